@@ -3,14 +3,18 @@ extern crate rocket;
 #[macro_use]
 extern crate dotenv_codegen;
 use dotenv;
+use nylas::adapters::rocket::WebhookHandler;
+use nylas::auth::generate_state;
 use nylas::client::Nylas;
 use nylas::messages::Message;
-use rocket::http::{Method, Status};
+use nylas::webhooks::Delta;
+use rocket::http::{Cookie, CookieJar, Method, SameSite, Status};
 use rocket::request::Outcome;
 use rocket::serde::json::Json;
+use rocket::serde::Deserialize;
 use rocket::{request::FromRequest, Request, State};
 use rocket_cors::{AllowedHeaders, AllowedOrigins};
-use std::sync::Arc;
+use std::time::Duration;
 
 #[rocket::main]
 async fn main() -> Result<(), rocket::Error> {
@@ -39,12 +43,13 @@ async fn main() -> Result<(), rocket::Error> {
     let client_secret = dotenv!("NYLAS_CLIENT_SECRET");
 
     let client = Nylas::new(client_id, client_secret, None).await.unwrap();
-    let client_arc = Arc::new(client);
     let routes = all_routes();
+    let webhooks = WebhookHandler::new(client_secret, handle_delta);
     rocket::build()
         .mount("/", routes)
+        .mount("/nylas/webhooks", webhooks)
         .attach(cors)
-        .manage(client_arc)
+        .manage(client)
         .launch()
         .await
         .expect("Launch Error");
@@ -60,11 +65,23 @@ fn all_routes() -> Vec<rocket::Route> {
     ]
 }
 
+// The cookie binding the signed state to the browser which started the authentication
+const SESSION_COOKIE: &str = "nylas_session";
+
 #[get("/nylas/generate-auth-token")]
-fn generate_auth_token(client: &State<Arc<Nylas>>) -> String {
+fn generate_auth_token(client: &State<Nylas>, cookies: &CookieJar<'_>) -> String {
     // Define authentication parameters
     let login_hint = Some("mahmoudddharmouchhh@gmail.com");
-    let state = Some("unique_identifier");
+    // A random state signed with the client secret, valid for 10 minutes and only accepted
+    // along with the nonce kept in the cookie of this browser
+    let session = generate_state();
+    let state = client.sign_state(&session, "", Duration::from_secs(600));
+    cookies.add(
+        Cookie::build((SESSION_COOKIE, session))
+            .http_only(true)
+            .same_site(SameSite::Lax),
+    );
+    let state = Some(state.as_str());
     let scopes = Some("email,calendar,contacts");
 
     // Generate an authentication URL
@@ -74,15 +91,33 @@ fn generate_auth_token(client: &State<Arc<Nylas>>) -> String {
     }
 }
 
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct Callback {
+    code: String,
+    state: String,
+}
+
 #[post(
     "/nylas/exchange-access-token",
     format = "application/json",
-    data = "<authorization_code>"
+    data = "<callback>"
 )]
-async fn exchange_access_token(client: &State<Arc<Nylas>>, authorization_code: String) -> String {
-    match client.exchange_access_token(&authorization_code).await {
-        Ok(access_token) => access_token,
-        Err(error) => error,
+async fn exchange_access_token(
+    client: &State<Nylas>,
+    cookies: &CookieJar<'_>,
+    callback: Json<Callback>,
+) -> Result<String, Status> {
+    // Reject the states which weren't issued to this browser
+    let session = cookies.get(SESSION_COOKIE).ok_or(Status::Forbidden)?;
+    client
+        .verify_state(session.value(), &callback.state)
+        .map_err(|_| Status::Forbidden)?;
+    cookies.remove(SESSION_COOKIE);
+
+    match client.exchange_access_token(&callback.code).await {
+        Ok(access_token) => Ok(access_token),
+        Err(error) => Ok(error),
     }
 }
 
@@ -103,17 +138,14 @@ impl<'r> FromRequest<'r> for AccessToken {
 
         match token_header {
             Some(token) => Outcome::Success(AccessToken { token }),
-            None => Outcome::Failure((Status::Unauthorized, ())),
+            None => Outcome::Error((Status::Unauthorized, ())),
         }
     }
 }
 
 #[get("/nylas/messages")]
-async fn get_all_messages(auth: AccessToken, client: &State<Arc<Nylas>>) -> Json<Vec<Message>> {
-    let mut client_with_token =
-        Nylas::new(&client.client_id, &client.client_secret, Some(&auth.token))
-            .await
-            .unwrap();
+async fn get_all_messages(auth: AccessToken, client: &State<Nylas>) -> Json<Vec<Message>> {
+    let client_with_token = client.with_access_token(&auth.token);
 
     // Call the all method to retrieve all messages
     let messages = client_with_token.messages().all().await;
@@ -125,11 +157,8 @@ async fn get_all_messages(auth: AccessToken, client: &State<Arc<Nylas>>) -> Json
 }
 
 #[get("/nylas/recent-message")]
-async fn get_first_message(auth: AccessToken, client: &State<Arc<Nylas>>) -> Json<Message> {
-    let mut client_with_token =
-        Nylas::new(&client.client_id, &client.client_secret, Some(&auth.token))
-            .await
-            .unwrap();
+async fn get_first_message(auth: AccessToken, client: &State<Nylas>) -> Json<Message> {
+    let client_with_token = client.with_access_token(&auth.token);
 
     let message_result = client_with_token.messages().first().await;
 
@@ -139,3 +168,11 @@ async fn get_first_message(auth: AccessToken, client: &State<Arc<Nylas>>) -> Jso
         Err(_err) => todo!(),
     }
 }
+
+async fn handle_delta(delta: Delta) {
+    match delta {
+        Delta::MessageCreated(data) => println!("New message: {}", data.object_data.id),
+        Delta::AccountInvalid(data) => println!("Account to reconnect: {}", data.object_data.id),
+        delta => println!("Received {:?}", delta.trigger()),
+    }
+}
@@ -1,15 +1,468 @@
+use crate::client::Nylas;
+use crate::timestamp::Timestamp;
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
 
 /// Struct representing an Nylas account.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+///
+/// Unknown fields are ignored and missing fields are left empty, so newer versions of the API
+/// don't break the deserialization.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::accounts::{Account, OrganizationUnit, Provider, SyncState};
+///
+/// let json = r#"{
+///     "id": "awa6ltos76vz5hvphkp8k17nt",
+///     "object": "account",
+///     "account_id": "awa6ltos76vz5hvphkp8k17nt",
+///     "name": "Dorothy Vaughan",
+///     "provider": "gmail",
+///     "organization_unit": "label",
+///     "sync_state": "running",
+///     "linked_at": 4102444800,
+///     "email_address": "dorothy@example.com",
+///     "billing_state": "paid"
+/// }"#;
+///
+/// let account: Account = serde_json::from_str(json).unwrap();
+/// assert_eq!(account.provider, Provider::Gmail);
+/// assert_eq!(account.organization_unit, OrganizationUnit::Label);
+/// assert_eq!(account.sync_state, SyncState::Running);
+/// assert_eq!(account.linked_at.unwrap().as_secs(), 4102444800);
+///
+/// let account: Account = serde_json::from_str(r#"{"id": "ID", "sync_state": "paused"}"#).unwrap();
+/// assert_eq!(account.sync_state, SyncState::Unknown("paused".to_string()));
+/// assert_eq!(account.linked_at, None);
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
 pub struct Account {
     pub id: String,
     pub object: String,
     pub account_id: String,
     pub name: String,
-    pub provider: String,
-    pub organization_unit: String,
-    pub sync_state: String,
-    pub linked_at: i32,
+    pub provider: Provider,
+    pub organization_unit: OrganizationUnit,
+    pub sync_state: SyncState,
+    pub linked_at: Option<Timestamp>,
     pub email_address: String,
 }
+
+/// Represents the way an account organizes its messages: with folders (IMAP, Exchange, ...)
+/// or with labels (Gmail).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum OrganizationUnit {
+    Folder,
+    Label,
+    Unknown(String),
+}
+
+impl Default for OrganizationUnit {
+    fn default() -> Self {
+        OrganizationUnit::Unknown(String::new())
+    }
+}
+
+impl From<&str> for OrganizationUnit {
+    fn from(name: &str) -> Self {
+        match name {
+            "folder" => OrganizationUnit::Folder,
+            "label" => OrganizationUnit::Label,
+            other => OrganizationUnit::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl FromStr for OrganizationUnit {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(OrganizationUnit::from(s))
+    }
+}
+
+impl fmt::Display for OrganizationUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrganizationUnit::Folder => write!(f, "folder"),
+            OrganizationUnit::Label => write!(f, "label"),
+            OrganizationUnit::Unknown(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Represents the sync state of an account.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum SyncState {
+    /// The account is being set up.
+    Initializing,
+    /// The messages of the account are being downloaded.
+    Downloading,
+    /// The account is in sync.
+    Running,
+    /// Some folders of the account failed to sync.
+    Partial,
+    /// The account is cancelled or was stopped after repeated errors.
+    Stopped,
+    /// The credentials of the account are no longer valid, the user must reconnect it.
+    Invalid,
+    /// The account hit an unexpected error.
+    Exception,
+    /// The account can't be synced, e.g. because IMAP is disabled.
+    SyncError,
+    Unknown(String),
+}
+
+impl SyncState {
+    /// Whether the user must authenticate again to resume the sync.
+    pub fn needs_reauthentication(&self) -> bool {
+        *self == SyncState::Invalid
+    }
+}
+
+impl Default for SyncState {
+    fn default() -> Self {
+        SyncState::Unknown(String::new())
+    }
+}
+
+impl From<&str> for SyncState {
+    fn from(name: &str) -> Self {
+        match name {
+            "initializing" => SyncState::Initializing,
+            "downloading" => SyncState::Downloading,
+            "running" => SyncState::Running,
+            "partial" => SyncState::Partial,
+            "stopped" => SyncState::Stopped,
+            "invalid" | "invalid-credentials" => SyncState::Invalid,
+            "exception" => SyncState::Exception,
+            "sync-error" => SyncState::SyncError,
+            other => SyncState::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for SyncState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncState::Initializing => write!(f, "initializing"),
+            SyncState::Downloading => write!(f, "downloading"),
+            SyncState::Running => write!(f, "running"),
+            SyncState::Partial => write!(f, "partial"),
+            SyncState::Stopped => write!(f, "stopped"),
+            SyncState::Invalid => write!(f, "invalid"),
+            SyncState::Exception => write!(f, "exception"),
+            SyncState::SyncError => write!(f, "sync-error"),
+            SyncState::Unknown(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Represents the provider of an account.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum Provider {
+    Gmail,
+    /// Microsoft 365 through the Microsoft Graph API.
+    Graph,
+    Office365,
+    Outlook,
+    /// Microsoft Exchange through Exchange ActiveSync.
+    Eas,
+    /// Microsoft Exchange through Exchange Web Services.
+    Ews,
+    Exchange,
+    Icloud,
+    Yahoo,
+    Aol,
+    Hotmail,
+    Imap,
+    Unknown(String),
+}
+
+impl Default for Provider {
+    fn default() -> Self {
+        Provider::Unknown(String::new())
+    }
+}
+
+impl From<&str> for Provider {
+    fn from(name: &str) -> Self {
+        match name {
+            "gmail" => Provider::Gmail,
+            "graph" => Provider::Graph,
+            "office365" => Provider::Office365,
+            "outlook" => Provider::Outlook,
+            "eas" => Provider::Eas,
+            "ews" => Provider::Ews,
+            "exchange" => Provider::Exchange,
+            "icloud" => Provider::Icloud,
+            "yahoo" => Provider::Yahoo,
+            "aol" => Provider::Aol,
+            "hotmail" => Provider::Hotmail,
+            "imap" => Provider::Imap,
+            other => Provider::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Provider::Gmail => write!(f, "gmail"),
+            Provider::Graph => write!(f, "graph"),
+            Provider::Office365 => write!(f, "office365"),
+            Provider::Outlook => write!(f, "outlook"),
+            Provider::Eas => write!(f, "eas"),
+            Provider::Ews => write!(f, "ews"),
+            Provider::Exchange => write!(f, "exchange"),
+            Provider::Icloud => write!(f, "icloud"),
+            Provider::Yahoo => write!(f, "yahoo"),
+            Provider::Aol => write!(f, "aol"),
+            Provider::Hotmail => write!(f, "hotmail"),
+            Provider::Imap => write!(f, "imap"),
+            Provider::Unknown(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+// The enums are (de)serialized through their names, unknown names included
+macro_rules! string_conversions {
+    ($($name:ident),*) => {
+        $(
+            impl From<String> for $name {
+                fn from(name: String) -> Self {
+                    $name::from(name.as_str())
+                }
+            }
+
+            impl From<$name> for String {
+                fn from(value: $name) -> Self {
+                    value.to_string()
+                }
+            }
+        )*
+    };
+}
+
+string_conversions!(OrganizationUnit, SyncState, Provider);
+
+/// An account of the application, as seen by the account management API.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ManagedAccount {
+    pub id: String,
+    #[serde(default)]
+    pub account_id: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub provider: Option<Provider>,
+    /// `paid`, `free`, `cancelled` or `deleted`.
+    #[serde(default)]
+    pub billing_state: Option<String>,
+    #[serde(default)]
+    pub sync_state: Option<SyncState>,
+    /// `password` or `oauth`.
+    #[serde(default)]
+    pub authentication_type: Option<String>,
+    #[serde(default)]
+    pub trial: Option<bool>,
+    #[serde(default)]
+    pub namespace_id: Option<String>,
+}
+
+/// Struct for managing all the accounts connected to the application.
+///
+/// Unlike `Nylas::account`, which returns the account of the access token, these requests are
+/// authenticated with the client ID and client secret stored on `Nylas` and don't require an
+/// access token.
+pub struct Accounts<'a> {
+    pub nylas: &'a Nylas,
+}
+
+impl<'a> Accounts<'a> {
+    /// The number of accounts requested per page by [`Accounts::all`].
+    pub const PAGE_SIZE: u32 = 100;
+
+    pub fn new(nylas: &'a Nylas) -> Self {
+        Accounts { nylas }
+    }
+
+    /// Retrieve a page of the accounts of the application.
+    ///
+    /// # Arguments
+    ///
+    /// - `limit`: The maximum number of accounts to return.
+    /// - `offset`: The number of accounts to skip.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use nylas::client::Nylas;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     match nylas.accounts().list(Some(50), Some(0)).await {
+    ///         Ok(accounts) => {
+    ///             for account in accounts {
+    ///                 println!("{:?}: {:?}", account.email, account.billing_state);
+    ///             }
+    ///         }
+    ///         Err(err) => eprintln!("Error: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn list(
+        &self,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<ManagedAccount>, String> {
+        let mut params = Vec::new();
+        if let Some(limit) = limit {
+            params.push(("limit", limit));
+        }
+        if let Some(offset) = offset {
+            params.push(("offset", offset));
+        }
+
+        let request = self
+            .nylas
+            .app_request(Method::GET, self.url(None)?)
+            .query(&params);
+        let response = self.nylas.send(request).await.map_err(|e| e.to_string())?;
+
+        if response.status().is_success() {
+            response
+                .json()
+                .await
+                .map_err(|e| format!("JSON Parsing Error: {:?}", e))
+        } else {
+            Err(format!("Request failed with status: {}", response.status()))
+        }
+    }
+
+    /// Retrieve all the accounts of the application, page by page.
+    pub async fn all(&self) -> Result<Vec<ManagedAccount>, String> {
+        let mut accounts = Vec::new();
+        loop {
+            let page = self
+                .list(Some(Self::PAGE_SIZE), Some(accounts.len() as u32))
+                .await?;
+            let count = page.len();
+            accounts.extend(page);
+            if count < Self::PAGE_SIZE as usize {
+                return Ok(accounts);
+            }
+        }
+    }
+
+    /// Retrieve a specific account by its ID.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `Some(account)` if found, `None` if the account doesn't exist, or an
+    /// error message.
+    pub async fn get(&self, id: &str) -> Result<Option<ManagedAccount>, String> {
+        let request = self.nylas.app_request(Method::GET, self.url(Some(id))?);
+        let response = self.nylas.send(request).await.map_err(|e| e.to_string())?;
+
+        if response.status().is_success() {
+            response
+                .json()
+                .await
+                .map(Some)
+                .map_err(|e| format!("JSON Parsing Error: {:?}", e))
+        } else if response.status() == reqwest::StatusCode::NOT_FOUND {
+            Ok(None)
+        } else {
+            Err(format!("Request failed with status: {}", response.status()))
+        }
+    }
+
+    /// Re-enable a cancelled account, billed again.
+    pub async fn upgrade(&self, id: &str) -> Result<(), String> {
+        let url = self.nylas.accounts_url(&[id, "upgrade"])?;
+        self.execute(Method::POST, &url, false).await
+    }
+
+    /// Cancel an account: it stops syncing and is no longer billed, but can be upgraded again.
+    pub async fn downgrade(&self, id: &str) -> Result<(), String> {
+        let url = self.nylas.accounts_url(&[id, "downgrade"])?;
+        self.execute(Method::POST, &url, false).await
+    }
+
+    /// Delete an account and all its data.
+    ///
+    /// Deleting an account which no longer exists succeeds, so that a deletion can be retried.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use nylas::accounts::SyncState;
+    /// use nylas::client::Nylas;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     // Offboard the accounts which stopped syncing
+    ///     for account in nylas.accounts().all().await.unwrap() {
+    ///         if account.sync_state == Some(SyncState::Invalid) {
+    ///             nylas.accounts().delete(&account.id).await.unwrap();
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn delete(&self, id: &str) -> Result<(), String> {
+        let url = self.url(Some(id))?;
+        self.execute(Method::DELETE, &url, true).await
+    }
+
+    /// Send a request which has no response body. `missing_ok` treats a `404` as a success.
+    async fn execute(&self, method: Method, url: &str, missing_ok: bool) -> Result<(), String> {
+        // Upgrading, downgrading or deleting an account twice has the same effect. A retried
+        // deletion whose first attempt went through finds the account gone, hence `missing_ok`.
+        let response = self
+            .nylas
+            .send_idempotent(self.nylas.app_request(method, url))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response.status().is_success()
+            || (missing_ok && response.status() == reqwest::StatusCode::NOT_FOUND)
+        {
+            Ok(())
+        } else {
+            Err(format!("Request failed with status: {}", response.status()))
+        }
+    }
+
+    fn url(&self, id: Option<&str>) -> Result<String, String> {
+        self.nylas.accounts_url(id.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::Nylas;
+
+    #[tokio::test]
+    async fn ids_are_a_single_path_segment() {
+        let nylas = Nylas::new("client", "secret", None).await.unwrap();
+        assert_eq!(
+            nylas.accounts().url(Some("a/b?c#d")).unwrap(),
+            "https://api.nylas.com/a/client/accounts/a%2Fb%3Fc%23d"
+        );
+        assert_eq!(
+            nylas.accounts_url(&["../x", "revoke-all"]).unwrap(),
+            "https://api.nylas.com/a/client/accounts/..%2Fx/revoke-all"
+        );
+    }
+}
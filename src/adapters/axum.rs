@@ -0,0 +1,249 @@
+//! Webhook receiver for [axum](https://docs.rs/axum).
+//!
+//! Use [`webhook_router`] to get a router handling the whole flow, or the
+//! [`VerifiedNotification`] extractor to write your own handler.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nylas::adapters::axum::webhook_router;
+//! use nylas::webhooks::Delta;
+//!
+//! let app: axum::Router = axum::Router::new().nest(
+//!     "/webhooks/nylas",
+//!     webhook_router("YOUR_CLIENT_SECRET", |delta: Delta| async move {
+//!         println!("{:?} on {:?}", delta.trigger(), delta.object_id());
+//!     }),
+//! );
+//! ```
+
+use super::{callback, dispatch, receive, Callback, Rejection, WebhookSecret};
+use crate::webhooks::{challenge_response, WebhookNotification, SIGNATURE_HEADER};
+use ::axum::async_trait;
+use ::axum::body::Bytes;
+use ::axum::extract::{FromRef, FromRequest, Request, State};
+use ::axum::http::{StatusCode, Uri};
+use ::axum::response::{IntoResponse, Response};
+use ::axum::routing::get;
+use ::axum::Router;
+use std::future::Future;
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        let status =
+            StatusCode::from_u16(self.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// Extractor for a notification whose signature was verified against the [`WebhookSecret`]
+/// available from the router state.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use axum::routing::post;
+/// use axum::Router;
+/// use nylas::adapters::axum::VerifiedNotification;
+/// use nylas::adapters::WebhookSecret;
+///
+/// async fn receive(VerifiedNotification(notification): VerifiedNotification) {
+///     for delta in notification.deltas {
+///         println!("{:?}", delta.trigger());
+///     }
+/// }
+///
+/// let app: Router = Router::new()
+///     .route("/webhooks/nylas", post(receive))
+///     .with_state(WebhookSecret::new("YOUR_CLIENT_SECRET"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VerifiedNotification(pub WebhookNotification);
+
+#[async_trait]
+impl<S> FromRequest<S> for VerifiedNotification
+where
+    S: Send + Sync,
+    WebhookSecret: FromRef<S>,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let secret = WebhookSecret::from_ref(state);
+        let signature = req
+            .headers()
+            .get(SIGNATURE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        receive(&secret.0, signature.as_deref(), &body)
+            .map(VerifiedNotification)
+            .map_err(IntoResponse::into_response)
+    }
+}
+
+#[derive(Clone)]
+struct WebhookState {
+    secret: WebhookSecret,
+    callback: Callback,
+}
+
+impl FromRef<WebhookState> for WebhookSecret {
+    fn from_ref(state: &WebhookState) -> Self {
+        state.secret.clone()
+    }
+}
+
+/// Build a router receiving Nylas webhooks on its root path.
+///
+/// - `GET /?challenge=...` answers the handshake performed when a webhook is created.
+/// - `POST /` verifies the signature, parses the notification, answers `200` and then calls
+///   `handler` once per delta, in order, in a background task. Invalid notifications are
+///   rejected with `401` or `400`.
+///
+/// # Arguments
+///
+/// - `client_secret`: The client secret of the application.
+/// - `handler`: An async callback receiving each delta.
+pub fn webhook_router<S, F, Fut>(client_secret: impl Into<String>, handler: F) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    F: Fn(crate::webhooks::Delta) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let state = WebhookState {
+        secret: WebhookSecret::new(client_secret),
+        callback: callback(handler),
+    };
+
+    Router::new()
+        .route("/", get(challenge).post(notification))
+        .with_state(state)
+}
+
+async fn challenge(uri: Uri) -> Response {
+    match uri.query().and_then(challenge_response) {
+        Some(challenge) => (StatusCode::OK, challenge).into_response(),
+        None => (StatusCode::BAD_REQUEST, "Missing challenge parameter.").into_response(),
+    }
+}
+
+async fn notification(
+    State(state): State<WebhookState>,
+    VerifiedNotification(notification): VerifiedNotification,
+) -> StatusCode {
+    dispatch(&state.callback, notification);
+    StatusCode::OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::Delta;
+    use ::axum::body::{to_bytes, Body};
+    use ::axum::http::Request;
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+    use std::sync::Arc;
+    use tokio::sync::{mpsc, Semaphore};
+    use tower::ServiceExt;
+
+    const SECRET: &str = "YOUR_CLIENT_SECRET";
+    const BODY: &str = r#"{"deltas": [{"date": 1602623196, "object": "message",
+        "type": "message.created", "object_data": {"account_id": "a1", "object": "message",
+        "id": "m1"}}]}"#;
+
+    fn sign(body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(body.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    // A router whose callback waits for the gate to open before passing the deltas on
+    fn router() -> (Router, Arc<Semaphore>, mpsc::UnboundedReceiver<Delta>) {
+        let gate = Arc::new(Semaphore::new(0));
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let waiting = gate.clone();
+        let router = webhook_router(SECRET, move |delta: Delta| {
+            let gate = waiting.clone();
+            let sender = sender.clone();
+            async move {
+                gate.acquire().await.unwrap().forget();
+                sender.send(delta).unwrap();
+            }
+        });
+        (router, gate, receiver)
+    }
+
+    fn post(body: &str, signature: Option<&str>) -> Request<Body> {
+        let mut request = Request::post("/");
+        if let Some(signature) = signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+        request.body(Body::from(body.to_string())).unwrap()
+    }
+
+    async fn send(router: &Router, request: Request<Body>) -> (StatusCode, String) {
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn the_challenge_is_echoed_back() {
+        let (router, _, _) = router();
+        let request = Request::get("/?challenge=abc%2B123")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(
+            send(&router, request).await,
+            (StatusCode::OK, "abc+123".to_string())
+        );
+
+        let request = Request::get("/").body(Body::empty()).unwrap();
+        assert_eq!(send(&router, request).await.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn unsigned_or_forged_notifications_are_unauthorized() {
+        let (router, _, _) = router();
+        assert_eq!(
+            send(&router, post(BODY, None)).await,
+            (
+                StatusCode::UNAUTHORIZED,
+                "Missing X-Nylas-Signature header.".to_string()
+            )
+        );
+        assert_eq!(
+            send(&router, post(BODY, Some(&sign("{}")))).await,
+            (
+                StatusCode::UNAUTHORIZED,
+                "Invalid webhook signature.".to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_payloads_are_bad_requests() {
+        let (router, _, _) = router();
+        let body = r#"{"deltas": "none"}"#;
+        let (status, _) = send(&router, post(body, Some(&sign(body)))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn the_callback_runs_after_the_acknowledgement() {
+        let (router, gate, mut deltas) = router();
+        let (status, _) = send(&router, post(BODY, Some(&sign(BODY)))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(deltas.try_recv().is_err());
+
+        gate.add_permits(1);
+        let delta = deltas.recv().await.unwrap();
+        assert_eq!(delta.message_id(), Some("m1"));
+    }
+}
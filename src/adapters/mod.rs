@@ -0,0 +1,98 @@
+//! Ready-made webhook receivers for web frameworks.
+//!
+//! Each adapter is behind a cargo feature of the same name (`axum`, `rocket`) and takes care of:
+//!
+//! 1. Answering the `GET ?challenge=` handshake Nylas performs when a webhook is created.
+//! 2. Verifying the `X-Nylas-Signature` of every notification with the client secret.
+//! 3. Parsing the notification into typed [`Delta`]s and handing them to a user callback.
+//!
+//! Nylas expects a `200` within a few seconds, and retries then disables webhooks whose endpoint
+//! is too slow. The notification is therefore acknowledged right away and the callback runs in
+//! a background task, one delta after the other. Deltas still being handled when the server
+//! shuts down are lost: persist them or hand them to a queue if they must not be missed.
+
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "rocket")]
+pub mod rocket;
+
+use crate::webhooks::{verify_signature, Delta, WebhookNotification};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// The client secret used to verify the signature of incoming notifications.
+#[derive(Debug, Clone)]
+pub struct WebhookSecret(pub String);
+
+impl WebhookSecret {
+    pub fn new(secret: impl Into<String>) -> Self {
+        WebhookSecret(secret.into())
+    }
+}
+
+/// A boxed callback receiving the deltas of verified notifications.
+pub(crate) type Callback =
+    Arc<dyn Fn(Delta) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+pub(crate) fn callback<F, Fut>(handler: F) -> Callback
+where
+    F: Fn(Delta) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    Arc::new(move |delta| Box::pin(handler(delta)))
+}
+
+/// Hand the deltas of a notification to the callback in a background task, in order.
+pub(crate) fn dispatch(callback: &Callback, notification: WebhookNotification) {
+    let callback = callback.clone();
+    tokio::spawn(async move {
+        for delta in notification.deltas {
+            callback(delta).await;
+        }
+    });
+}
+
+/// Reasons a notification is rejected by the adapters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    /// The `X-Nylas-Signature` header is missing.
+    MissingSignature,
+    /// The signature doesn't match the body.
+    InvalidSignature,
+    /// The body isn't a valid notification.
+    InvalidPayload(String),
+}
+
+impl Rejection {
+    /// The HTTP status code the adapters respond with.
+    pub fn status(&self) -> u16 {
+        match self {
+            Rejection::MissingSignature | Rejection::InvalidSignature => 401,
+            Rejection::InvalidPayload(_) => 400,
+        }
+    }
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::MissingSignature => write!(f, "Missing X-Nylas-Signature header."),
+            Rejection::InvalidSignature => write!(f, "Invalid webhook signature."),
+            Rejection::InvalidPayload(error) => write!(f, "Invalid webhook payload: {}", error),
+        }
+    }
+}
+
+/// Verify and parse a notification, the common path of every adapter.
+pub(crate) fn receive(
+    secret: &str,
+    signature: Option<&str>,
+    body: &[u8],
+) -> Result<WebhookNotification, Rejection> {
+    let signature = signature.ok_or(Rejection::MissingSignature)?;
+    if !verify_signature(secret, body, signature) {
+        return Err(Rejection::InvalidSignature);
+    }
+    WebhookNotification::from_slice(body).map_err(Rejection::InvalidPayload)
+}
@@ -0,0 +1,258 @@
+//! Webhook receiver for [rocket](https://docs.rs/rocket).
+//!
+//! Mount a [`WebhookHandler`] to get routes handling the whole flow, or use the
+//! [`VerifiedNotification`] data guard to write your own route.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nylas::adapters::rocket::WebhookHandler;
+//! use nylas::webhooks::Delta;
+//!
+//! #[rocket::launch]
+//! fn rocket() -> _ {
+//!     rocket::build().mount(
+//!         "/webhooks/nylas",
+//!         WebhookHandler::new("YOUR_CLIENT_SECRET", |delta: Delta| async move {
+//!             println!("{:?} on {:?}", delta.trigger(), delta.object_id());
+//!         }),
+//!     )
+//! }
+//! ```
+
+use super::{callback, dispatch, receive, Callback, Rejection, WebhookSecret};
+use crate::webhooks::{challenge_response, Delta, WebhookNotification, SIGNATURE_HEADER};
+use ::rocket::data::{self, Data, FromData, Limits};
+use ::rocket::http::{Method, Status};
+use ::rocket::route::{self, Handler, Route};
+use ::rocket::{async_trait, Request};
+use std::future::Future;
+
+/// Data guard for a notification whose signature was verified against the [`WebhookSecret`]
+/// held in managed state.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use nylas::adapters::rocket::VerifiedNotification;
+/// use nylas::adapters::WebhookSecret;
+///
+/// #[rocket::post("/webhooks/nylas", data = "<notification>")]
+/// fn receive(notification: VerifiedNotification) {
+///     for delta in notification.0.deltas {
+///         println!("{:?}", delta.trigger());
+///     }
+/// }
+///
+/// #[rocket::launch]
+/// fn rocket() -> _ {
+///     rocket::build()
+///         .manage(WebhookSecret::new("YOUR_CLIENT_SECRET"))
+///         .mount("/", rocket::routes![receive])
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct VerifiedNotification(pub WebhookNotification);
+
+impl VerifiedNotification {
+    async fn from_request<'r>(
+        secret: &str,
+        req: &'r Request<'_>,
+        data: Data<'r>,
+    ) -> Result<Self, (Status, Rejection)> {
+        let limit = req.limits().get("json").unwrap_or(Limits::JSON);
+        let body = data.open(limit).into_bytes().await.map_err(|e| {
+            (
+                Status::BadRequest,
+                Rejection::InvalidPayload(format!("{:?}", e)),
+            )
+        })?;
+        if !body.is_complete() {
+            return Err((
+                Status::PayloadTooLarge,
+                Rejection::InvalidPayload(format!("Body exceeds {} bytes.", limit)),
+            ));
+        }
+
+        let signature = req.headers().get_one(SIGNATURE_HEADER);
+        receive(secret, signature, &body.into_inner())
+            .map(VerifiedNotification)
+            .map_err(|rejection| {
+                let status = Status::from_code(rejection.status()).unwrap_or(Status::BadRequest);
+                (status, rejection)
+            })
+    }
+}
+
+#[async_trait]
+impl<'r> FromData<'r> for VerifiedNotification {
+    type Error = Rejection;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        let secret = match req.rocket().state::<WebhookSecret>() {
+            Some(secret) => secret.0.clone(),
+            None => {
+                return data::Outcome::Error((
+                    Status::InternalServerError,
+                    Rejection::InvalidPayload("WebhookSecret is not managed.".to_string()),
+                ))
+            }
+        };
+
+        match VerifiedNotification::from_request(&secret, req, data).await {
+            Ok(notification) => data::Outcome::Success(notification),
+            Err(error) => data::Outcome::Error(error),
+        }
+    }
+}
+
+/// Custom handler receiving Nylas webhooks on the path it is mounted on.
+///
+/// - `GET /?challenge=...` answers the handshake performed when a webhook is created.
+/// - `POST /` verifies the signature, parses the notification, answers `200` and then calls the
+///   callback once per delta, in order, in a background task. Invalid notifications are
+///   rejected with `401` or `400`.
+#[derive(Clone)]
+pub struct WebhookHandler {
+    secret: WebhookSecret,
+    callback: Callback,
+}
+
+impl WebhookHandler {
+    /// Create a handler verifying notifications with `client_secret` and handing their deltas
+    /// to `handler`.
+    pub fn new<F, Fut>(client_secret: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(Delta) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        WebhookHandler {
+            secret: WebhookSecret::new(client_secret),
+            callback: callback(handler),
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for WebhookHandler {
+    async fn handle<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> route::Outcome<'r> {
+        if req.method() == Method::Get {
+            return match req
+                .uri()
+                .query()
+                .and_then(|q| challenge_response(q.as_str()))
+            {
+                Some(challenge) => route::Outcome::from(req, challenge),
+                None => route::Outcome::error(Status::BadRequest),
+            };
+        }
+
+        match VerifiedNotification::from_request(&self.secret.0, req, data).await {
+            Ok(VerifiedNotification(notification)) => {
+                dispatch(&self.callback, notification);
+                route::Outcome::from(req, Status::Ok)
+            }
+            Err((status, _)) => route::Outcome::error(status),
+        }
+    }
+}
+
+impl From<WebhookHandler> for Vec<Route> {
+    fn from(handler: WebhookHandler) -> Self {
+        vec![
+            Route::new(Method::Get, "/", handler.clone()),
+            Route::new(Method::Post, "/", handler),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::rocket::http::Header;
+    use ::rocket::local::asynchronous::Client;
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+    use std::sync::Arc;
+    use tokio::sync::{mpsc, Semaphore};
+
+    const SECRET: &str = "YOUR_CLIENT_SECRET";
+    const BODY: &str = r#"{"deltas": [{"date": 1602623196, "object": "message",
+        "type": "message.created", "object_data": {"account_id": "a1", "object": "message",
+        "id": "m1"}}]}"#;
+
+    fn sign(body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(body.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    // A client whose callback waits for the gate to open before passing the deltas on
+    async fn client() -> (Client, Arc<Semaphore>, mpsc::UnboundedReceiver<Delta>) {
+        let gate = Arc::new(Semaphore::new(0));
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let waiting = gate.clone();
+        let handler = WebhookHandler::new(SECRET, move |delta: Delta| {
+            let gate = waiting.clone();
+            let sender = sender.clone();
+            async move {
+                gate.acquire().await.unwrap().forget();
+                sender.send(delta).unwrap();
+            }
+        });
+        let client = Client::tracked(::rocket::build().mount("/hooks", handler))
+            .await
+            .unwrap();
+        (client, gate, receiver)
+    }
+
+    async fn post(client: &Client, body: &str, signature: Option<String>) -> Status {
+        let mut request = client.post("/hooks").body(body);
+        if let Some(signature) = signature {
+            request = request.header(Header::new(SIGNATURE_HEADER, signature));
+        }
+        request.dispatch().await.status()
+    }
+
+    #[tokio::test]
+    async fn the_challenge_is_echoed_back() {
+        let (client, _, _) = client().await;
+        let response = client.get("/hooks?challenge=abc%2B123").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().await.unwrap(), "abc+123");
+
+        let response = client.get("/hooks").dispatch().await;
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[tokio::test]
+    async fn unsigned_or_forged_notifications_are_unauthorized() {
+        let (client, _, _) = client().await;
+        assert_eq!(post(&client, BODY, None).await, Status::Unauthorized);
+        assert_eq!(
+            post(&client, BODY, Some(sign("{}"))).await,
+            Status::Unauthorized
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_payloads_are_bad_requests() {
+        let (client, _, _) = client().await;
+        let body = r#"{"deltas": "none"}"#;
+        assert_eq!(
+            post(&client, body, Some(sign(body))).await,
+            Status::BadRequest
+        );
+    }
+
+    #[tokio::test]
+    async fn the_callback_runs_after_the_acknowledgement() {
+        let (client, gate, mut deltas) = client().await;
+        assert_eq!(post(&client, BODY, Some(sign(BODY))).await, Status::Ok);
+        assert!(deltas.try_recv().is_err());
+
+        gate.add_permits(1);
+        let delta = deltas.recv().await.unwrap();
+        assert_eq!(delta.message_id(), Some("m1"));
+    }
+}
@@ -0,0 +1,673 @@
+//! Hosted authentication.
+//!
+//! The hosted authentication flow redirects the user to Nylas, which asks them to log in with
+//! their provider and to grant the requested scopes, then redirects them back to your
+//! application with an authorization code (or an access token with [`ResponseType::Token`]).
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nylas::auth::AuthenticationOptions;
+//! use nylas::client::Nylas;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+//!
+//!     let options = AuthenticationOptions::new()
+//!         .with_login_hint("user+work@example.com")
+//!         .with_scopes(&["email.read_only", "calendar"])
+//!         .with_provider("gmail");
+//!
+//!     let url = nylas
+//!         .authentication_url_with("http://localhost:3000/callback?app=mail", &options)
+//!         .unwrap();
+//!     assert_eq!(
+//!         url,
+//!         "https://api.nylas.com/oauth/authorize?client_id=YOUR_CLIENT_ID\
+//!          &redirect_uri=http%3A%2F%2Flocalhost%3A3000%2Fcallback%3Fapp%3Dmail\
+//!          &response_type=code&scopes=email.read_only%2Ccalendar\
+//!          &login_hint=user%2Bwork%40example.com&provider=gmail"
+//!     );
+//! }
+//! ```
+
+use crate::timestamp::Timestamp;
+use base64::{engine::general_purpose, Engine as _};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The URL of the hosted authentication page.
+pub const AUTHORIZE_URL: &str = "https://api.nylas.com/oauth/authorize";
+
+/// The URL exchanging authorization codes and refresh tokens for access tokens.
+pub const TOKEN_URL: &str = "https://api.nylas.com/oauth/token";
+
+/// The URL revoking the access token the request is authenticated with.
+pub const REVOKE_URL: &str = "https://api.nylas.com/oauth/revoke";
+
+/// What Nylas sends back to the redirect URI once the user is authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResponseType {
+    /// An authorization code, exchanged server-side for an access token.
+    #[default]
+    Code,
+    /// An access token, sent in the fragment of the redirect URI. Only use it for clients
+    /// which can't keep the client secret.
+    Token,
+}
+
+impl fmt::Display for ResponseType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResponseType::Code => write!(f, "code"),
+            ResponseType::Token => write!(f, "token"),
+        }
+    }
+}
+
+/// The optional parameters of the hosted authentication URL.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthenticationOptions {
+    pub response_type: ResponseType,
+    /// The scopes requested, e.g. `email.read_only` or `calendar`.
+    pub scopes: Vec<String>,
+    /// The email address pre-filled on the authentication page.
+    pub login_hint: Option<String>,
+    /// An opaque value sent back to the redirect URI, to protect against CSRF.
+    pub state: Option<String>,
+    /// The provider to authenticate with, e.g. `gmail`, `office365`, `exchange` or `imap`,
+    /// which skips the provider detection.
+    pub provider: Option<String>,
+    /// Whether to redirect to the redirect URI with an error instead of showing it to the user.
+    pub redirect_on_error: bool,
+    /// How the authentication page prompts the user, e.g. `select_provider` or `detect`.
+    pub prompt: Option<String>,
+    /// The PKCE code challenge, see [`Pkce`].
+    pub code_challenge: Option<String>,
+}
+
+impl AuthenticationOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_response_type(mut self, response_type: ResponseType) -> Self {
+        self.response_type = response_type;
+        self
+    }
+
+    pub fn with_scopes<S: AsRef<str>>(mut self, scopes: &[S]) -> Self {
+        self.scopes = scopes.iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
+    pub fn with_login_hint(mut self, login_hint: &str) -> Self {
+        self.login_hint = Some(login_hint.to_string());
+        self
+    }
+
+    pub fn with_state(mut self, state: &str) -> Self {
+        self.state = Some(state.to_string());
+        self
+    }
+
+    pub fn with_provider(mut self, provider: &str) -> Self {
+        self.provider = Some(provider.to_string());
+        self
+    }
+
+    pub fn with_redirect_on_error(mut self, redirect_on_error: bool) -> Self {
+        self.redirect_on_error = redirect_on_error;
+        self
+    }
+
+    pub fn with_prompt(mut self, prompt: &str) -> Self {
+        self.prompt = Some(prompt.to_string());
+        self
+    }
+
+    /// Send the code challenge of a PKCE verifier, which then has to be given to
+    /// `Nylas::exchange_authorization_code_with_verifier`.
+    pub fn with_pkce(mut self, pkce: &Pkce) -> Self {
+        self.code_challenge = Some(pkce.challenge.clone());
+        self
+    }
+}
+
+/// Validate a list of scopes and join them the way Nylas expects them.
+///
+/// Scopes are trimmed, duplicates are dropped and the scopes are joined with commas.
+///
+/// # Errors
+///
+/// Returns an error if a scope is empty or contains anything but lowercase letters, digits,
+/// dots and underscores.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::auth::join_scopes;
+///
+/// assert_eq!(
+///     join_scopes(&["email.read_only", " calendar", "email.read_only"]).unwrap(),
+///     "email.read_only,calendar"
+/// );
+/// assert!(join_scopes(&["email", ""]).is_err());
+/// assert!(join_scopes(&["email calendar"]).is_err());
+/// ```
+pub fn join_scopes<S: AsRef<str>>(scopes: &[S]) -> Result<String, String> {
+    let mut joined: Vec<&str> = Vec::new();
+    for scope in scopes {
+        let scope = scope.as_ref().trim();
+        let valid = !scope.is_empty()
+            && scope
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '_');
+        if !valid {
+            return Err(format!("Invalid scope: {:?}", scope));
+        }
+        if !joined.contains(&scope) {
+            joined.push(scope);
+        }
+    }
+    Ok(joined.join(","))
+}
+
+/// A PKCE (Proof Key for Code Exchange) code verifier and its `S256` code challenge.
+///
+/// PKCE lets clients which can't keep the client secret, such as desktop and mobile
+/// applications, use hosted authentication: the challenge is sent with the authentication URL
+/// and only the holder of the verifier can exchange the authorization code.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::auth::{AuthenticationOptions, Pkce};
+///
+/// let pkce = Pkce::new();
+/// assert_eq!(pkce.verifier.len(), 43);
+///
+/// // Keep the verifier until the callback, send the challenge
+/// let options = AuthenticationOptions::new().with_pkce(&pkce);
+/// assert_eq!(options.code_challenge, Some(pkce.challenge));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    /// The method of the challenge, sent as `code_challenge_method`.
+    pub const METHOD: &'static str = "S256";
+
+    /// Generate a random verifier of 32 bytes, and its challenge.
+    pub fn new() -> Self {
+        let verifier = random_token(32);
+        Pkce {
+            challenge: code_challenge(&verifier),
+            verifier,
+        }
+    }
+
+    /// Use an existing verifier, e.g. after restoring it from the session of the user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the verifier isn't 43 to 128 characters long or contains anything but
+    /// letters, digits, `-`, `.`, `_` and `~`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nylas::auth::Pkce;
+    ///
+    /// // The example of RFC 7636
+    /// let pkce = Pkce::from_verifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk").unwrap();
+    /// assert_eq!(pkce.challenge, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+    ///
+    /// assert!(Pkce::from_verifier("too short").is_err());
+    /// ```
+    pub fn from_verifier(verifier: &str) -> Result<Self, String> {
+        let valid = (43..=128).contains(&verifier.len())
+            && verifier
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~'));
+        if !valid {
+            return Err("Invalid PKCE code verifier.".to_string());
+        }
+
+        Ok(Pkce {
+            verifier: verifier.to_string(),
+            challenge: code_challenge(verifier),
+        })
+    }
+}
+
+impl Default for Pkce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The `S256` code challenge of a verifier: its SHA-256 digest, base64url-encoded without
+/// padding.
+pub fn code_challenge(verifier: &str) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// A base64url-encoded string of random bytes from the random number generator of the
+/// operating system.
+pub(crate) fn random_token(bytes: usize) -> String {
+    let mut buffer = vec![0; bytes];
+    getrandom::getrandom(&mut buffer).expect("The system random number generator failed");
+    general_purpose::URL_SAFE_NO_PAD.encode(buffer)
+}
+
+/// Generate a random `state` for the authentication URL.
+///
+/// Store it in the session of the user before redirecting them, and check that the `state` sent
+/// back to the redirect URI is the same to protect the callback against CSRF.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::auth::generate_state;
+///
+/// let state = generate_state();
+/// assert_eq!(state.len(), 43);
+/// assert_ne!(state, generate_state());
+/// ```
+pub fn generate_state() -> String {
+    random_token(32)
+}
+
+/// The content of a state signed with [`sign_state`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedState {
+    /// The random part of the state, unique to every authentication.
+    pub nonce: String,
+    /// The data of the application carried by the state.
+    pub data: String,
+    /// When the state expires, in seconds since the Unix epoch.
+    pub expires_at: u64,
+}
+
+/// Generate a random `state` carrying data of the application, signed with the client secret
+/// and valid for `ttl`.
+///
+/// The state is bound to `session`, a value kept in the browser session of the user, e.g. the
+/// session id or a random nonce from [`generate_state`] stored in a cookie. [`verify_state`]
+/// only accepts the state along with the same value, so a state obtained in another session
+/// can't be injected into the callback of the user.
+///
+/// The state has the form `{nonce}.{expires_at}.{data}.{signature}`, where the data is
+/// base64url-encoded and the signature is the base64url-encoded HMAC-SHA256 of the rest and of
+/// the session value, keyed with the client secret. The session value itself is left out of the
+/// state, but the data is only encoded, not encrypted: don't put secrets in it.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::auth::{generate_state, sign_state, verify_state};
+/// use std::time::Duration;
+///
+/// // Kept in a cookie until the callback
+/// let session = generate_state();
+/// let state = sign_state("YOUR_CLIENT_SECRET", &session, "/settings", Duration::from_secs(600));
+///
+/// let verified = verify_state("YOUR_CLIENT_SECRET", &session, &state).unwrap();
+/// assert_eq!(verified.data, "/settings");
+///
+/// assert!(verify_state("YOUR_CLIENT_SECRET", &generate_state(), &state).is_err());
+/// assert!(verify_state("ANOTHER_SECRET", &session, &state).is_err());
+/// assert!(verify_state("YOUR_CLIENT_SECRET", &session, &state.replace('.', "_")).is_err());
+/// ```
+pub fn sign_state(secret: &str, session: &str, data: &str, ttl: Duration) -> String {
+    let expires_at = (SystemTime::now() + ttl)
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let payload = format!(
+        "{}.{}.{}",
+        generate_state(),
+        expires_at,
+        general_purpose::URL_SAFE_NO_PAD.encode(data)
+    );
+    let signature = general_purpose::URL_SAFE_NO_PAD
+        .encode(state_mac(secret, session, &payload).finalize().into_bytes());
+    format!("{}.{}", payload, signature)
+}
+
+/// Check the signature and the expiry of a state generated with [`sign_state`] for the same
+/// `session` value.
+///
+/// # Errors
+///
+/// Returns an error if the session value is empty, or if the state is malformed, wasn't signed
+/// with the client secret for this session value, or expired.
+pub fn verify_state(secret: &str, session: &str, state: &str) -> Result<SignedState, String> {
+    let invalid = || "Invalid state.".to_string();
+
+    // Without a session value, any state signed without one would be accepted
+    if session.is_empty() {
+        return Err(invalid());
+    }
+    let (payload, signature) = state.rsplit_once('.').ok_or_else(invalid)?;
+    let signature = general_purpose::URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|_| invalid())?;
+    // `verify_slice` compares the tags in constant time
+    state_mac(secret, session, payload)
+        .verify_slice(&signature)
+        .map_err(|_| invalid())?;
+
+    let mut parts = payload.splitn(3, '.');
+    let (nonce, expires_at, data) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(nonce), Some(expires_at), Some(data)) => (nonce, expires_at, data),
+        _ => return Err(invalid()),
+    };
+    let expires_at: u64 = expires_at.parse().map_err(|_| invalid())?;
+    let data = general_purpose::URL_SAFE_NO_PAD
+        .decode(data)
+        .ok()
+        .and_then(|data| String::from_utf8(data).ok())
+        .ok_or_else(invalid)?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    if expires_at <= now {
+        return Err("Expired state.".to_string());
+    }
+
+    Ok(SignedState {
+        nonce: nonce.to_string(),
+        data,
+        expires_at,
+    })
+}
+
+fn state_mac(secret: &str, session: &str, payload: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    // The payload never holds a NUL byte, which separates it from the session value
+    mac.update(payload.as_bytes());
+    mac.update(b"\0");
+    mac.update(session.as_bytes());
+    mac
+}
+
+/// The response of a token exchange.
+///
+/// Unknown fields are ignored and missing optional fields are left empty. The `Debug` output
+/// redacts the access and refresh tokens.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::auth::TokenResponse;
+///
+/// let json = r#"{
+///     "access_token": "ACCESS_TOKEN",
+///     "account_id": "ACCOUNT_ID",
+///     "email_address": "user@example.com",
+///     "provider": "gmail",
+///     "token_type": "bearer",
+///     "scope": "email.read_only,calendar",
+///     "expires_in": 3600
+/// }"#;
+///
+/// let token: TokenResponse = serde_json::from_str(json).unwrap();
+/// assert_eq!(token.account_id.as_deref(), Some("ACCOUNT_ID"));
+/// assert_eq!(token.scopes(), vec!["email.read_only", "calendar"]);
+/// assert!(!token.is_expired());
+/// assert!(!format!("{:?}", token).contains("ACCESS_TOKEN"));
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "RawTokenResponse")]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub account_id: Option<String>,
+    pub email_address: Option<String>,
+    pub provider: Option<String>,
+    pub token_type: Option<String>,
+    /// The granted scopes, separated by commas.
+    pub scope: Option<String>,
+    pub refresh_token: Option<String>,
+    /// The lifetime of the access token in seconds, if it expires.
+    pub expires_in: Option<u64>,
+    /// When the access token expires, if it does.
+    ///
+    /// Nylas only sends `expires_in`: the expiry is computed when the response is received, and
+    /// serialized along with the token so that it is kept when the token is read back.
+    pub expires_at: Option<Timestamp>,
+    /// When the response was received or read back. It is neither serialized nor compared.
+    #[serde(skip)]
+    pub received_at: SystemTime,
+}
+
+// The fields of a token response, with the expiry of a serialized token if it has one
+#[derive(Deserialize)]
+struct RawTokenResponse {
+    access_token: String,
+    #[serde(default)]
+    account_id: Option<String>,
+    #[serde(default)]
+    email_address: Option<String>,
+    #[serde(default)]
+    provider: Option<String>,
+    #[serde(default)]
+    token_type: Option<String>,
+    #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+    #[serde(default)]
+    expires_at: Option<Timestamp>,
+}
+
+impl From<RawTokenResponse> for TokenResponse {
+    fn from(raw: RawTokenResponse) -> Self {
+        let received_at = SystemTime::now();
+        let expires_at = raw.expires_at.or_else(|| {
+            // Lifetimes too long to be represented never expire
+            raw.expires_in
+                .and_then(|expires_in| received_at.checked_add(Duration::from_secs(expires_in)))
+                .map(Timestamp::from)
+        });
+        TokenResponse {
+            access_token: raw.access_token,
+            account_id: raw.account_id,
+            email_address: raw.email_address,
+            provider: raw.provider,
+            token_type: raw.token_type,
+            scope: raw.scope,
+            refresh_token: raw.refresh_token,
+            expires_in: raw.expires_in,
+            expires_at,
+            received_at,
+        }
+    }
+}
+
+impl PartialEq for TokenResponse {
+    fn eq(&self, other: &Self) -> bool {
+        self.access_token == other.access_token
+            && self.account_id == other.account_id
+            && self.email_address == other.email_address
+            && self.provider == other.provider
+            && self.token_type == other.token_type
+            && self.scope == other.scope
+            && self.refresh_token == other.refresh_token
+            && self.expires_in == other.expires_in
+            && self.expires_at == other.expires_at
+    }
+}
+
+impl Eq for TokenResponse {}
+
+impl TokenResponse {
+    /// The granted scopes.
+    pub fn scopes(&self) -> Vec<&str> {
+        self.scope
+            .as_deref()
+            .unwrap_or_default()
+            .split([',', ' '])
+            .filter(|scope| !scope.is_empty())
+            .collect()
+    }
+
+    /// Whether the access token expired.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Timestamp::now())
+    }
+}
+
+impl fmt::Debug for TokenResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenResponse")
+            .field("access_token", &"[REDACTED]")
+            .field("account_id", &self.account_id)
+            .field("email_address", &self.email_address)
+            .field("provider", &self.provider)
+            .field("token_type", &self.token_type)
+            .field("scope", &self.scope)
+            .field(
+                "refresh_token",
+                &self.refresh_token.as_ref().map(|_| "[REDACTED]"),
+            )
+            .field("expires_in", &self.expires_in)
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+/// Information about an access token of an account.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::auth::TokenInfo;
+///
+/// let json = r#"{
+///     "created_at": 1563496685,
+///     "scopes": "calendar,email,contacts",
+///     "state": "valid",
+///     "updated_at": 1563496685
+/// }"#;
+///
+/// let info: TokenInfo = serde_json::from_str(json).unwrap();
+/// assert!(info.is_valid());
+/// assert_eq!(info.scopes(), vec!["calendar", "email", "contacts"]);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TokenInfo {
+    /// The granted scopes, separated by commas.
+    #[serde(default)]
+    pub scopes: Option<String>,
+    /// `valid` or `invalid`.
+    pub state: String,
+    /// When the token was created.
+    #[serde(default)]
+    pub created_at: Option<Timestamp>,
+    /// When the token was last updated.
+    #[serde(default)]
+    pub updated_at: Option<Timestamp>,
+}
+
+impl TokenInfo {
+    /// The granted scopes.
+    pub fn scopes(&self) -> Vec<&str> {
+        self.scopes
+            .as_deref()
+            .unwrap_or_default()
+            .split([',', ' '])
+            .filter(|scope| !scope.is_empty())
+            .collect()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.state == "valid"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialized_token_keeps_its_expiry() {
+        let token: TokenResponse =
+            serde_json::from_str(r#"{"access_token": "a1", "expires_in": 3600}"#).unwrap();
+        let expires_at = token.expires_at.unwrap();
+        assert!(expires_at > Timestamp::now());
+
+        let json = serde_json::to_string(&token).unwrap();
+        assert!(!json.contains("received_at"));
+        let read_back: TokenResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(read_back.expires_at, Some(expires_at));
+        assert_eq!(read_back, token);
+        assert_eq!(
+            serde_json::from_str::<TokenResponse>(&json).unwrap(),
+            read_back
+        );
+    }
+
+    #[test]
+    fn persisted_token_expires_when_read_back() {
+        let json = r#"{"access_token": "a1", "expires_in": 3600, "expires_at": 1700000000}"#;
+        let token: TokenResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(token.expires_at, Some(Timestamp(1700000000)));
+        assert!(token.is_expired());
+    }
+
+    #[test]
+    fn tokens_without_a_representable_expiry_never_expire() {
+        let token: TokenResponse = serde_json::from_str(r#"{"access_token": "a1"}"#).unwrap();
+        assert_eq!(token.expires_at, None);
+        assert!(!token.is_expired());
+
+        let json = format!(r#"{{"access_token": "a1", "expires_in": {}}}"#, u64::MAX);
+        let token: TokenResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(token.expires_at, None);
+    }
+
+    #[test]
+    fn signed_state_is_bound_to_the_session() {
+        let ttl = Duration::from_secs(600);
+        let state = sign_state("SECRET", "session-1", "/settings", ttl);
+
+        let verified = verify_state("SECRET", "session-1", &state).unwrap();
+        assert_eq!(verified.data, "/settings");
+        assert_eq!(verified.nonce.len(), 43);
+        // The session value isn't carried by the state
+        assert!(!state.contains("session-1"));
+
+        assert_eq!(
+            verify_state("SECRET", "session-2", &state).unwrap_err(),
+            "Invalid state."
+        );
+        assert_eq!(
+            verify_state("SECRET", "", &sign_state("SECRET", "", "", ttl)).unwrap_err(),
+            "Invalid state."
+        );
+    }
+
+    #[test]
+    fn expired_state_is_rejected() {
+        let state = sign_state("SECRET", "session-1", "", Duration::ZERO);
+        assert_eq!(
+            verify_state("SECRET", "session-1", &state).unwrap_err(),
+            "Expired state."
+        );
+    }
+}
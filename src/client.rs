@@ -1,13 +1,33 @@
-use crate::accounts::Account;
+use crate::accounts::{Account, Accounts};
+use crate::auth::{self, AuthenticationOptions, Pkce, SignedState, TokenInfo, TokenResponse};
+use crate::deltas::Deltas;
+use crate::events::Events;
+use crate::mailbox::Mailbox;
 use crate::messages::Messages;
+use crate::middleware::Middleware;
+use crate::native_auth::NativeAuth;
+use crate::rate_limit::{RateLimit, RateLimiter};
+use crate::retry::RetryPolicy;
+#[cfg(feature = "tracing")]
+use crate::telemetry;
+use crate::token::{IssuedTokens, TokenProvider};
+use crate::webhooks::{self, Webhooks};
 use base64::{engine::general_purpose, Engine as _};
-use std::collections::HashMap;
+use reqwest::header::AUTHORIZATION;
+use reqwest::{IntoUrl, Method, Request, RequestBuilder, Response, StatusCode};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard};
+use std::time::Duration;
 use url::Url;
 
 /// The `Nylas` struct provides all methods available in the Nylas API.
 ///
 /// This struct currently allows you to create authentication URLs for initiating the OAuth 2.0 flow with the Nylas API.
 ///
+/// A `Nylas` instance is cheap to clone: the clones share the same HTTP connection pool,
+/// middleware and rate limiter, so a single client can serve many concurrent tasks.
+///
 /// # Examples
 ///
 /// To create a new `Nylas` instance with your client ID and client secret:
@@ -20,12 +40,82 @@ use url::Url;
 ///
 /// let nylas = Nylas::new(client_id, client_secret, None);
 /// ```
+///
+/// The client can be shared across threads and tasks:
+///
+/// ```
+/// use nylas::client::Nylas;
+///
+/// fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+/// assert_shareable::<Nylas>();
+/// ```
+#[derive(Clone)]
 pub struct Nylas {
-    pub client_id: String,
-    pub client_secret: String,
-    pub account: Option<Account>,
-    pub access_token: Option<String>,
-    pub messages: Option<Messages<'static>>,
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    client_id: String,
+    client_secret: String,
+    access_token: Option<String>,
+    token_provider: Option<Arc<dyn TokenProvider>>,
+    issued_tokens: Arc<IssuedTokens>,
+    account: RwLock<Option<Account>>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    rate_limit: Mutex<Option<RateLimit>>,
+    http: reqwest::Client,
+    middleware: Vec<Arc<dyn Middleware>>,
+}
+
+// Lets the builders copy the state of a client which was already cloned
+impl Clone for Inner {
+    fn clone(&self) -> Self {
+        Inner {
+            client_id: self.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+            access_token: self.access_token.clone(),
+            token_provider: self.token_provider.clone(),
+            issued_tokens: self.issued_tokens.clone(),
+            account: RwLock::new(read(&self.account).clone()),
+            retry_policy: self.retry_policy.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            rate_limit: Mutex::new(lock(&self.rate_limit).clone()),
+            http: self.http.clone(),
+            middleware: self.middleware.clone(),
+        }
+    }
+}
+
+/// Debug output of a `Nylas` instance, with the client secret and the access token redacted.
+///
+/// # Examples
+///
+/// ```
+/// use nylas::client::Nylas;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+///
+///     let debug = format!("{:?}", nylas);
+///     assert!(debug.contains("YOUR_CLIENT_ID"));
+///     assert!(!debug.contains("YOUR_CLIENT_SECRET"));
+/// }
+/// ```
+impl fmt::Debug for Nylas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Nylas")
+            .field("client_id", &self.inner.client_id)
+            .field("client_secret", &"[REDACTED]")
+            .field("account", &*read(&self.inner.account))
+            .field(
+                "access_token",
+                &self.inner.access_token.as_ref().map(|_| "[REDACTED]"),
+            )
+            .field("retry_policy", &self.inner.retry_policy)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Nylas {
@@ -57,15 +147,23 @@ impl Nylas {
         client_secret: &str,
         access_token: Option<&str>,
     ) -> Result<Self, String> {
-        let mut nylas = Nylas {
-            client_id: client_id.to_string(),
-            client_secret: client_secret.to_string(),
-            access_token: access_token.map(|s| s.to_string()),
-            account: None,
-            messages: None,
+        let nylas = Nylas {
+            inner: Arc::new(Inner {
+                client_id: client_id.to_string(),
+                client_secret: client_secret.to_string(),
+                access_token: access_token.map(|s| s.to_string()),
+                token_provider: None,
+                issued_tokens: Arc::default(),
+                account: RwLock::new(None),
+                retry_policy: RetryPolicy::default(),
+                rate_limiter: None,
+                rate_limit: Mutex::new(None),
+                http: reqwest::Client::new(),
+                middleware: Vec::new(),
+            }),
         };
 
-        if let Some(_) = nylas.access_token {
+        if nylas.inner.access_token.is_some() {
             if let Err(error) = nylas.account().await {
                 return Err(format!("Error initializing Nylas: {}", error));
             }
@@ -74,6 +172,132 @@ impl Nylas {
         Ok(nylas)
     }
 
+    /// Create a new `Nylas` instance sharing the configuration of this one (HTTP client, retry
+    /// policy, rate limiter and middleware) but authenticated with another access token.
+    ///
+    /// Unlike [`Nylas::new`], the account details are not fetched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let app = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     let nylas = app.with_access_token("YOUR_ACCESS_TOKEN");
+    ///     assert_eq!(nylas.access_token(), Some("YOUR_ACCESS_TOKEN"));
+    ///     assert_eq!(app.access_token(), None);
+    /// }
+    /// ```
+    pub fn with_access_token(&self, access_token: &str) -> Self {
+        let mut inner = (*self.inner).clone();
+        inner.access_token = Some(access_token.to_string());
+        inner.token_provider = None;
+        inner.account = RwLock::new(None);
+        inner.rate_limit = Mutex::new(None);
+        Nylas {
+            inner: Arc::new(inner),
+        }
+    }
+
+    /// Ask a [`TokenProvider`] for the access token before every request, instead of using the
+    /// fixed access token of this instance.
+    ///
+    /// When Nylas answers `401 Unauthorized`, the token is invalidated and the request is sent
+    /// once more if the provider has another token.
+    pub fn with_token_provider<P: TokenProvider + 'static>(mut self, provider: P) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.token_provider = Some(Arc::new(provider));
+        inner.issued_tokens = Arc::default();
+        inner.account = RwLock::new(None);
+        inner.rate_limit = Mutex::new(None);
+        self
+    }
+
+    /// Replace the policy used to retry failed requests.
+    ///
+    /// Like the other builders, it only affects this instance: configure the client before
+    /// cloning it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    /// use nylas::retry::RetryPolicy;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None)
+    ///         .await
+    ///         .unwrap()
+    ///         .with_retry_policy(RetryPolicy::default().with_max_attempts(5));
+    /// }
+    /// ```
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        Arc::make_mut(&mut self.inner).retry_policy = retry_policy;
+        self
+    }
+
+    /// Limit the requests sent by this instance with a client-side token bucket per account.
+    ///
+    /// The same limiter can be shared by several instances to apply a single quota to all of
+    /// them. Until the account is fetched, requests are limited by the access token they carry.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        Arc::make_mut(&mut self.inner).rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Add a middleware run around every request, see [`Middleware`].
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        Arc::make_mut(&mut self.inner)
+            .middleware
+            .push(Arc::new(middleware));
+        self
+    }
+
+    /// Replace the HTTP client used to send the requests, e.g. to configure timeouts or a proxy.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        Arc::make_mut(&mut self.inner).http = http;
+        self
+    }
+
+    /// The client ID of the application.
+    pub fn client_id(&self) -> &str {
+        &self.inner.client_id
+    }
+
+    /// The client secret of the application, which must never be logged.
+    pub fn client_secret(&self) -> &str {
+        &self.inner.client_secret
+    }
+
+    /// The fixed access token of this instance, if any.
+    pub fn access_token(&self) -> Option<&str> {
+        self.inner.access_token.as_deref()
+    }
+
+    /// The account details loaded by [`Nylas::account`], if any.
+    pub fn cached_account(&self) -> Option<Account> {
+        read(&self.inner.account).clone()
+    }
+
+    /// How failed requests are retried, see [`RetryPolicy`].
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.inner.retry_policy
+    }
+
+    /// The client-side limiter applied to every request, see [`RateLimiter`].
+    pub fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.inner.rate_limiter.as_ref()
+    }
+
+    /// The rate-limit headers of the last response received from Nylas, if it had any.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        lock(&self.inner.rate_limit).clone()
+    }
+
     /// Generate an authentication URL for initiating the OAuth 2.0 flow.
     ///
     /// The authentication URL can be opened in a web browser to allow users to grant
@@ -95,10 +319,11 @@ impl Nylas {
     /// Returns an error if any of the following conditions are not met:
     /// 1. The client ID and client secret are not provided.
     /// 2. The redirect URI is not a valid URL.
+    /// 3. The comma-separated scopes are valid.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// use nylas::client::Nylas;
     ///
     /// #[tokio::main]
@@ -126,47 +351,98 @@ impl Nylas {
         state: Option<&str>,
         scopes: Option<&str>,
     ) -> Result<String, String> {
-        if self.client_id.is_empty() || self.client_secret.is_empty() {
-            return Err("Client ID and Client Secret must not be empty.".to_string());
-        }
-
-        if !Url::parse(redirect_uri).is_ok() {
-            return Err("Invalid redirect URI.".to_string());
+        let mut options = AuthenticationOptions::new();
+        options.login_hint = login_hint.map(|s| s.to_string());
+        options.state = state.map(|s| s.to_string());
+        if let Some(scopes) = scopes {
+            options.scopes = scopes.split(',').map(|s| s.to_string()).collect();
         }
 
-        let mut params: HashMap<&str, String> = HashMap::new();
-        params.insert("client_id", self.client_id.clone());
-        params.insert("redirect_uri", redirect_uri.to_string());
-        params.insert("response_type", "code".to_string());
-
-        if let Some(login_hint) = login_hint {
-            params.insert("login_hint", login_hint.to_string());
-        }
+        self.authentication_url_with(redirect_uri, &options)
+    }
 
-        if let Some(state) = state {
-            params.insert("state", state.to_string());
+    /// Generate an authentication URL with all the options of the hosted authentication, see
+    /// [`AuthenticationOptions`].
+    ///
+    /// The parameters are percent-encoded and always appear in the same order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the following conditions are not met:
+    /// 1. The client ID and client secret are not provided. The client secret isn't needed with
+    ///    a PKCE code challenge.
+    /// 2. The redirect URI is not a valid URL.
+    /// 3. The scopes are valid, see [`auth::join_scopes`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nylas::auth::{AuthenticationOptions, ResponseType};
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     let options = AuthenticationOptions::new()
+    ///         .with_response_type(ResponseType::Token)
+    ///         .with_redirect_on_error(true)
+    ///         .with_prompt("select_provider");
+    ///
+    ///     let url = nylas.authentication_url_with("myapp://callback", &options).unwrap();
+    ///     assert!(url.ends_with("&response_type=token&redirect_on_error=true&prompt=select_provider"));
+    /// }
+    /// ```
+    pub fn authentication_url_with(
+        &self,
+        redirect_uri: &str,
+        options: &AuthenticationOptions,
+    ) -> Result<String, String> {
+        // Public clients using PKCE don't have the client secret
+        if options.code_challenge.is_some() {
+            if self.inner.client_id.is_empty() {
+                return Err("Client ID must not be empty.".to_string());
+            }
+        } else if self.inner.client_id.is_empty() || self.inner.client_secret.is_empty() {
+            return Err("Client ID and Client Secret must not be empty.".to_string());
         }
 
-        if let Some(scopes) = scopes {
-            params.insert("scopes", scopes.to_string());
+        if Url::parse(redirect_uri).is_err() {
+            return Err("Invalid redirect URI.".to_string());
         }
 
-        // Build the URL
-        let base_url = "https://api.nylas.com/oauth/authorize";
-        let mut url = String::from(base_url);
-        url.push('?');
+        let mut url = Url::parse(auth::AUTHORIZE_URL).map_err(|e| e.to_string())?;
+        {
+            let mut params = url.query_pairs_mut();
+            params.append_pair("client_id", &self.inner.client_id);
+            params.append_pair("redirect_uri", redirect_uri);
+            params.append_pair("response_type", &options.response_type.to_string());
 
-        for (key, value) in params.iter() {
-            url.push_str(key);
-            url.push_str("=");
-            url.push_str(value);
-            url.push('&');
+            if !options.scopes.is_empty() {
+                params.append_pair("scopes", &auth::join_scopes(&options.scopes)?);
+            }
+            if let Some(login_hint) = &options.login_hint {
+                params.append_pair("login_hint", login_hint);
+            }
+            if let Some(state) = &options.state {
+                params.append_pair("state", state);
+            }
+            if let Some(provider) = &options.provider {
+                params.append_pair("provider", provider);
+            }
+            if options.redirect_on_error {
+                params.append_pair("redirect_on_error", "true");
+            }
+            if let Some(prompt) = &options.prompt {
+                params.append_pair("prompt", prompt);
+            }
+            if let Some(code_challenge) = &options.code_challenge {
+                params.append_pair("code_challenge", code_challenge);
+                params.append_pair("code_challenge_method", Pkce::METHOD);
+            }
         }
 
-        // Remove the trailing '&' character
-        url.pop();
-
-        Ok(url)
+        Ok(url.to_string())
     }
 
     /// Exchange the authorization code for an access token using hosted authentication.
@@ -189,7 +465,7 @@ impl Nylas {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// use nylas::client::Nylas;
     ///
     /// #[tokio::main]
@@ -208,54 +484,317 @@ impl Nylas {
     /// }
     /// ```
     pub async fn exchange_access_token(&self, authorization_code: &str) -> Result<String, String> {
-        if self.client_id.is_empty() || self.client_secret.is_empty() {
+        let token = self.exchange_authorization_code(authorization_code).await?;
+        Ok(token.access_token)
+    }
+
+    /// Exchange the authorization code for the full token response of hosted authentication,
+    /// with the account, the granted scopes and the refresh token if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the following conditions are not met:
+    /// 1. The client ID and client secret are not provided.
+    /// 2. The `authorization_code` is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let app = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     let token = app.exchange_authorization_code("YOUR_AUTHORIZATION_CODE").await.unwrap();
+    ///     println!("Connected {:?} with {:?}", token.email_address, token.scopes());
+    ///
+    ///     let nylas = app.with_token(&token);
+    ///     let messages = nylas.messages().all().await;
+    /// }
+    /// ```
+    pub async fn exchange_authorization_code(
+        &self,
+        authorization_code: &str,
+    ) -> Result<TokenResponse, String> {
+        self.request_token(&[
+            ("grant_type", "authorization_code"),
+            ("code", authorization_code),
+        ])
+        .await
+    }
+
+    /// Exchange the authorization code of a PKCE authentication for the token response.
+    ///
+    /// The client secret is sent when this instance has one, but public clients can leave it
+    /// empty: the verifier proves that the code was requested by this client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the following conditions are not met:
+    /// 1. The client ID is not provided.
+    /// 2. The `authorization_code` is not valid or wasn't requested with the challenge of the
+    ///    `code_verifier`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::auth::{AuthenticationOptions, Pkce};
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // A desktop application only knows its client ID
+    ///     let app = Nylas::new("YOUR_CLIENT_ID", "", None).await.unwrap();
+    ///
+    ///     let pkce = Pkce::new();
+    ///     let options = AuthenticationOptions::new().with_pkce(&pkce);
+    ///     let url = app.authentication_url_with("http://127.0.0.1:8910/callback", &options);
+    ///
+    ///     // ... then, once redirected to the callback
+    ///     let token = app
+    ///         .exchange_authorization_code_with_verifier("YOUR_AUTHORIZATION_CODE", &pkce.verifier)
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub async fn exchange_authorization_code_with_verifier(
+        &self,
+        authorization_code: &str,
+        code_verifier: &str,
+    ) -> Result<TokenResponse, String> {
+        self.request_token(&[
+            ("grant_type", "authorization_code"),
+            ("code", authorization_code),
+            ("code_verifier", code_verifier),
+        ])
+        .await
+    }
+
+    /// Get a new access token with a refresh token.
+    ///
+    /// See [`token::RefreshingToken`](crate::token::RefreshingToken) to refresh the access
+    /// token of a `Nylas` instance automatically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the following conditions are not met:
+    /// 1. The client ID and client secret are not provided.
+    /// 2. The `refresh_token` is not valid.
+    pub async fn refresh_access_token(&self, refresh_token: &str) -> Result<TokenResponse, String> {
+        self.request_token(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ])
+        .await
+    }
+
+    /// Create a new `Nylas` instance sharing the configuration of this one, authenticated with
+    /// the access token of a token response.
+    ///
+    /// See [`Nylas::with_access_token`].
+    pub fn with_token(&self, token: &TokenResponse) -> Self {
+        self.with_access_token(&token.access_token)
+    }
+
+    /// Revoke an access token, e.g. when a user disconnects their account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token was already revoked or is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let app = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     if let Err(error) = app.revoke_token("ACCESS_TOKEN").await {
+    ///         eprintln!("Error: {}", error);
+    ///     }
+    /// }
+    /// ```
+    pub async fn revoke_token(&self, access_token: &str) -> Result<(), String> {
+        // The token revokes itself
+        let request = self
+            .inner
+            .http
+            .request(Method::POST, auth::REVOKE_URL)
+            .header("Accept", "application/json")
+            .header("Authorization", format!("Bearer {}", access_token));
+        let response = self
+            .send(request)
+            .await
+            .map_err(|e| format!("Request Error: {:?}", e))?;
+
+        Self::revoked(response).await
+    }
+
+    /// Revoke all the access tokens of an account, except `keep_access_token` if given.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the following conditions are not met:
+    /// 1. The client ID and client secret are not provided.
+    /// 2. The account exists.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let app = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     // Sign the user out of every other device
+    ///     app.revoke_all_tokens("ACCOUNT_ID", Some("CURRENT_ACCESS_TOKEN"))
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub async fn revoke_all_tokens(
+        &self,
+        account_id: &str,
+        keep_access_token: Option<&str>,
+    ) -> Result<(), String> {
+        let url = self.accounts_url(&[account_id, "revoke-all"])?;
+        let mut body = serde_json::Map::new();
+        if let Some(keep_access_token) = keep_access_token {
+            body.insert("keep_access_token".to_string(), keep_access_token.into());
+        }
+
+        let response = self
+            .send(self.app_request(Method::POST, url).json(&body))
+            .await
+            .map_err(|e| format!("Request Error: {:?}", e))?;
+
+        Self::revoked(response).await
+    }
+
+    /// Get the scopes, state and timestamps of an access token of an account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the following conditions are not met:
+    /// 1. The client ID and client secret are not provided.
+    /// 2. The access token belongs to the account.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let app = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     let info = app.token_info("ACCOUNT_ID", "ACCESS_TOKEN").await.unwrap();
+    ///     println!("{} with {:?}", info.state, info.scopes());
+    /// }
+    /// ```
+    pub async fn token_info(
+        &self,
+        account_id: &str,
+        access_token: &str,
+    ) -> Result<TokenInfo, String> {
+        let url = self.accounts_url(&[account_id, "token-info"])?;
+        let body = serde_json::json!({ "access_token": access_token });
+
+        let response = self
+            .send_idempotent(self.app_request(Method::POST, url).json(&body))
+            .await
+            .map_err(|e| format!("Request Error: {:?}", e))?;
+
+        if response.status().is_success() {
+            response
+                .json()
+                .await
+                .map_err(|e| format!("JSON Parsing Error: {:?}", e))
+        } else {
+            Err(format!("Request failed with status: {}", response.status()))
+        }
+    }
+
+    /// The URL of the application's accounts followed by `segments`, each percent-encoded so
+    /// that an ID containing `/` or `?` stays a single path segment.
+    pub(crate) fn accounts_url(&self, segments: &[&str]) -> Result<String, String> {
+        if self.inner.client_id.is_empty() || self.inner.client_secret.is_empty() {
             return Err("Client ID and Client Secret must not be empty.".to_string());
         }
 
-        let mut params: HashMap<&str, String> = HashMap::new();
-        params.insert("client_id", self.client_id.clone());
-        params.insert("client_secret", self.client_secret.clone());
-        params.insert("grant_type", "authorization_code".to_string());
-        params.insert("code", authorization_code.to_string());
+        let mut url = Url::parse("https://api.nylas.com/a").map_err(|e| e.to_string())?;
+        url.path_segments_mut()
+            .map_err(|_| "Cannot add segments to the accounts URL.".to_string())?
+            .push(&self.inner.client_id)
+            .push("accounts")
+            .extend(segments);
+        Ok(url.to_string())
+    }
 
-        // Build the URL
-        let base_url = "https://api.nylas.com/oauth/token";
+    /// Check the `{"success": true}` response of a revocation.
+    async fn revoked(response: Response) -> Result<(), String> {
+        if !response.status().is_success() {
+            return Err(format!("Request failed with status: {}", response.status()));
+        }
 
-        // Base64 encode the client secret
-        let encoded_client_secret = general_purpose::STANDARD.encode(self.client_secret.clone());
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
+        match body.get("success").and_then(|success| success.as_bool()) {
+            Some(false) => Err("Nylas did not confirm the revocation.".to_string()),
+            _ => Ok(()),
+        }
+    }
 
-        // Make the POST request
-        let client = reqwest::Client::new();
-        let response = client
-            .post(base_url)
-            .header("Authorization", format!("Basic {}", encoded_client_secret))
-            .header("Accept", "application/json")
-            .form(&params)
-            .send()
+    /// Send a request to the token endpoint, authenticated with the client ID and secret.
+    ///
+    /// Public clients without a client secret can only use grants with a PKCE code verifier.
+    async fn request_token(&self, grant: &[(&str, &str)]) -> Result<TokenResponse, String> {
+        let public = self.inner.client_secret.is_empty();
+        let pkce = grant.iter().any(|(key, _)| *key == "code_verifier");
+        if self.inner.client_id.is_empty() || (public && !pkce) {
+            return Err("Client ID and Client Secret must not be empty.".to_string());
+        }
+
+        let mut params = vec![("client_id", self.inner.client_id.as_str())];
+        let request = if public {
+            self.inner
+                .http
+                .request(Method::POST, auth::TOKEN_URL)
+                .header("Accept", "application/json")
+        } else {
+            params.push(("client_secret", self.inner.client_secret.as_str()));
+            self.app_request(Method::POST, auth::TOKEN_URL)
+        };
+        params.extend_from_slice(grant);
+
+        let response = self
+            .send(request.form(&params))
             .await
             .map_err(|e| format!("Request Error: {:?}", e))?;
 
         if response.status().is_success() {
-            let data: HashMap<String, String> = response
+            response
                 .json()
                 .await
-                .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
-            if let Some(access_token) = data.get("access_token") {
-                return Ok(access_token.to_string());
-            } else {
-                return Err("Access token not found in the response.".to_string());
-            }
+                .map_err(|e| format!("JSON Parsing Error: {:?}", e))
         } else {
             // change this to return the response error message
             let error_message = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown Error".to_string());
-            return Err(format!("HTTP Error: - {}", error_message));
+            Err(format!("HTTP Error: - {}", error_message))
         }
     }
 
-    /// Get account details for the authenticated user and store them in the `account` member.
+    /// Get account details for the authenticated user and cache them, see
+    /// [`Nylas::cached_account`].
     ///
     /// # Returns
     ///
@@ -269,7 +808,7 @@ impl Nylas {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// use nylas::client::Nylas;
     ///
     /// #[tokio::main]
@@ -280,23 +819,19 @@ impl Nylas {
     ///
     ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
     ///
-    ///     println!("Account Details: {:?}", nylas.account);
+    ///     println!("Account Details: {:?}", nylas.cached_account());
     /// }
     /// ```
-    pub async fn account(&mut self) -> Result<(), String> {
-        if self.client_id.is_empty() || self.client_secret.is_empty() {
+    pub async fn account(&self) -> Result<Account, String> {
+        if self.inner.client_id.is_empty() || self.inner.client_secret.is_empty() {
             return Err("Client ID and Client Secret must not be empty.".to_string());
         }
 
-        if let Some(access_token) = &self.access_token {
+        if self.inner.access_token.is_some() || self.inner.token_provider.is_some() {
             // Build the URL
             let base_url = "https://api.nylas.com/account";
-            let client = reqwest::Client::new();
-            let response = client
-                .get(base_url)
-                .header("Authorization", format!("Bearer {}", access_token))
-                .header("Accept", "application/json")
-                .send()
+            let response = self
+                .send(self.request(Method::GET, base_url).await?)
                 .await
                 .map_err(|e| format!("Request Error: {:?}", e))?;
 
@@ -305,8 +840,12 @@ impl Nylas {
                     .json()
                     .await
                     .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
-                self.account = Some(account);
-                Ok(())
+                *self
+                    .inner
+                    .account
+                    .write()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(account.clone());
+                Ok(account)
             } else {
                 Err(format!("HTTP Error: {}", response.status()))
             }
@@ -315,12 +854,31 @@ impl Nylas {
         }
     }
 
+    /// Returns an `Accounts` struct associated with this `Nylas` instance, which provides methods
+    /// for managing all the accounts connected to the application.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     let accounts = nylas.accounts().all().await;
+    /// }
+    /// ```
+    pub fn accounts(&self) -> Accounts<'_> {
+        Accounts { nylas: self }
+    }
+
     /// Returns a `Messages` struct associated with this `Nylas` instance, which provides methods
     /// for working with Nylas messages.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// use nylas::client::Nylas;
     ///
     /// #[tokio::main]
@@ -329,12 +887,482 @@ impl Nylas {
     ///     let client_secret = "YOUR_CLIENT_SECRET";
     ///     let access_token = "YOUR_ACCESS_TOKEN";
     ///
-    ///     let mut nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
     ///
     ///     let messages = nylas.messages();
     /// }
     /// ```
-    pub fn messages(&mut self) -> Messages {
+    pub fn messages(&self) -> Messages<'_> {
         Messages { nylas: self }
     }
+
+    /// Returns a `Deltas` struct associated with this `Nylas` instance, which provides methods
+    /// for following the changes of the account with cursors.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client_id = "YOUR_CLIENT_ID";
+    ///     let client_secret = "YOUR_CLIENT_SECRET";
+    ///     let access_token = "YOUR_ACCESS_TOKEN";
+    ///
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///
+    ///     let cursor = nylas.deltas().latest_cursor().await;
+    /// }
+    /// ```
+    pub fn deltas(&self) -> Deltas<'_> {
+        Deltas { nylas: self }
+    }
+
+    /// Returns an `Events` struct associated with this `Nylas` instance, which provides methods
+    /// for working with Nylas calendar events.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client_id = "YOUR_CLIENT_ID";
+    ///     let client_secret = "YOUR_CLIENT_SECRET";
+    ///     let access_token = "YOUR_ACCESS_TOKEN";
+    ///
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///
+    ///     let events = nylas.events();
+    /// }
+    /// ```
+    pub fn events(&self) -> Events<'_> {
+        Events { nylas: self }
+    }
+
+    /// Returns a `Mailbox` struct associated with this `Nylas` instance, which provides methods
+    /// for working with folders or labels depending on the connected account.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    /// use nylas::mailbox::Role;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client_id = "YOUR_CLIENT_ID";
+    ///     let client_secret = "YOUR_CLIENT_SECRET";
+    ///     let access_token = "YOUR_ACCESS_TOKEN";
+    ///
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///
+    ///     let inbox = nylas.mailbox().find_by_role(Role::Inbox).await;
+    /// }
+    /// ```
+    pub fn mailbox(&self) -> Mailbox<'_> {
+        Mailbox { nylas: self }
+    }
+
+    /// Returns a `NativeAuth` struct associated with this `Nylas` instance, which provides
+    /// methods for connecting accounts with their credentials, without any browser.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    /// use nylas::native_auth::{NativeAuthentication, ProviderSettings};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     let settings = ProviderSettings::Imap {
+    ///         imap_host: "imap.example.com".to_string(),
+    ///         imap_port: 993,
+    ///         imap_username: "jane@example.com".to_string(),
+    ///         imap_password: "PASSWORD".to_string(),
+    ///         smtp_host: "smtp.example.com".to_string(),
+    ///         smtp_port: 465,
+    ///         smtp_username: "jane@example.com".to_string(),
+    ///         smtp_password: "PASSWORD".to_string(),
+    ///         ssl_required: true,
+    ///     };
+    ///     let request = NativeAuthentication::new("Jane Doe", "jane@example.com", settings);
+    ///
+    ///     let token = nylas.native_auth().authenticate(&request).await;
+    /// }
+    /// ```
+    pub fn native_auth(&self) -> NativeAuth<'_> {
+        NativeAuth { nylas: self }
+    }
+
+    /// Returns a `Webhooks` struct associated with this `Nylas` instance, which provides methods
+    /// for managing the webhooks of the application.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client_id = "YOUR_CLIENT_ID";
+    ///     let client_secret = "YOUR_CLIENT_SECRET";
+    ///
+    ///     let nylas = Nylas::new(client_id, client_secret, None).await.unwrap();
+    ///
+    ///     let webhooks = nylas.webhooks().all().await;
+    /// }
+    /// ```
+    pub fn webhooks(&self) -> Webhooks<'_> {
+        Webhooks { nylas: self }
+    }
+
+    /// Verifies the `X-Nylas-Signature` of a webhook notification with the client secret of this
+    /// `Nylas` instance.
+    ///
+    /// See [`webhooks::verify_signature`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nylas::client::Nylas;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     let body = br#"{"deltas":[]}"#;
+    ///     let signature = "SIGNATURE_FROM_THE_HEADER";
+    ///
+    ///     if !nylas.verify_webhook_signature(body, signature) {
+    ///         eprintln!("Invalid webhook signature");
+    ///     }
+    /// }
+    /// ```
+    pub fn verify_webhook_signature(&self, body: &[u8], signature: &str) -> bool {
+        webhooks::verify_signature(&self.inner.client_secret, body, signature)
+    }
+
+    /// Generate a `state` carrying data of the application, bound to a value of the session of
+    /// the user and signed with the client secret of this `Nylas` instance.
+    ///
+    /// See [`auth::sign_state`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nylas::auth::{generate_state, AuthenticationOptions};
+    /// use nylas::client::Nylas;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     // A random nonce kept in a cookie of the user until the callback
+    ///     let session = generate_state();
+    ///     let state = nylas.sign_state(&session, "/settings", Duration::from_secs(600));
+    ///     let options = AuthenticationOptions::new().with_state(&state);
+    ///     let url = nylas.authentication_url_with("http://localhost:3000/callback", &options);
+    ///
+    ///     // In the callback, with the cookie and the `state` query parameter
+    ///     let verified = nylas.verify_state(&session, &state).unwrap();
+    ///     assert_eq!(verified.data, "/settings");
+    /// }
+    /// ```
+    pub fn sign_state(&self, session: &str, data: &str, ttl: Duration) -> String {
+        auth::sign_state(&self.inner.client_secret, session, data, ttl)
+    }
+
+    /// Check a `state` generated with [`Nylas::sign_state`] for the same session value.
+    ///
+    /// See [`auth::verify_state`] for details.
+    pub fn verify_state(&self, session: &str, state: &str) -> Result<SignedState, String> {
+        auth::verify_state(&self.inner.client_secret, session, state)
+    }
+
+    /// Start a request authenticated with the access token of the account.
+    ///
+    /// The token provider, if any, is preferred to the fixed access token.
+    pub(crate) async fn request<U: IntoUrl>(
+        &self,
+        method: Method,
+        url: U,
+    ) -> Result<RequestBuilder, String> {
+        let access_token = match &self.inner.token_provider {
+            Some(provider) => {
+                let access_token = provider.token().await?;
+                self.inner.issued_tokens.record(&access_token);
+                access_token
+            }
+            None => self
+                .access_token()
+                .ok_or("Access token not provided")?
+                .to_string(),
+        };
+        Ok(self
+            .inner
+            .http
+            .request(method, url)
+            .header("Accept", "application/json")
+            .header("Authorization", format!("Bearer {}", access_token)))
+    }
+
+    /// Start a request authenticated with the client secret of the application.
+    pub(crate) fn app_request<U: IntoUrl>(&self, method: Method, url: U) -> RequestBuilder {
+        // The client secret is the username of the basic auth, the password is left empty
+        let credentials =
+            general_purpose::STANDARD.encode(format!("{}:", self.inner.client_secret));
+        self.inner
+            .http
+            .request(method, url)
+            .header("Accept", "application/json")
+            .header("Authorization", format!("Basic {}", credentials))
+    }
+
+    /// Send a request through the middleware, retrying it according to the retry policy.
+    pub(crate) async fn send(&self, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+        self.execute(request, false).await
+    }
+
+    /// Send a request which is safe to retry whatever its method.
+    pub(crate) async fn send_idempotent(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<Response, reqwest::Error> {
+        self.execute(request, true).await
+    }
+
+    async fn execute(
+        &self,
+        request: RequestBuilder,
+        idempotent: bool,
+    ) -> Result<Response, reqwest::Error> {
+        let mut request = request.build()?;
+        // The middleware sees a request once, even when it is sent again with a fresh token
+        for middleware in &self.inner.middleware {
+            middleware.before_request(&mut request);
+        }
+
+        let (sent, result) = match &self.inner.token_provider {
+            Some(provider) => {
+                self.authenticate(provider.as_ref(), request, idempotent)
+                    .await
+            }
+            None => self.dispatch(request, idempotent).await,
+        };
+        match &result {
+            Ok(response) => {
+                for middleware in self.inner.middleware.iter().rev() {
+                    middleware.after_response(&sent, response);
+                }
+            }
+            Err(error) => {
+                for middleware in self.inner.middleware.iter().rev() {
+                    middleware.on_error(&sent, error);
+                }
+            }
+        }
+        result
+    }
+
+    // Send a request authenticated by the token provider, once more if its token is rejected
+    async fn authenticate(
+        &self,
+        provider: &dyn TokenProvider,
+        request: Request,
+        idempotent: bool,
+    ) -> (Request, Result<Response, reqwest::Error>) {
+        let retry = request.try_clone();
+        let (sent, result) = self.dispatch(request, idempotent).await;
+        let response = match result {
+            Ok(response) if response.status() == StatusCode::UNAUTHORIZED => response,
+            result => return (sent, result),
+        };
+
+        // Send the request once more if the provider has a fresh token
+        let mut retry = match retry {
+            Some(retry) => retry,
+            None => return (sent, Ok(response)),
+        };
+        // Requests authenticated with another token, e.g. the one being revoked, are left alone
+        let rejected = match bearer_token(&retry) {
+            Some(rejected) if self.inner.issued_tokens.contains(&rejected) => rejected,
+            _ => return (sent, Ok(response)),
+        };
+        if !provider.invalidate(&rejected) {
+            return (sent, Ok(response));
+        }
+        let value = match provider.token().await {
+            Ok(token) if token != rejected => format!("Bearer {}", token).parse().ok(),
+            _ => None,
+        };
+        match value {
+            Some(value) => {
+                retry.headers_mut().insert(AUTHORIZATION, value);
+                self.dispatch(retry, idempotent).await
+            }
+            None => (sent, Ok(response)),
+        }
+    }
+
+    // Send a request as prepared by the middleware, returning it without its body
+    async fn dispatch(
+        &self,
+        request: Request,
+        idempotent: bool,
+    ) -> (Request, Result<Response, reqwest::Error>) {
+        // What the middleware gets to see once the request is sent, without the body
+        let mut sent = reqwest::Request::new(request.method().clone(), request.url().clone());
+        *sent.headers_mut() = request.headers().clone();
+
+        // Requests are limited per account, by the access token they carry before the account is
+        // known, or per application for requests authenticated with the client secret
+        let account_id = read(&self.inner.account)
+            .as_ref()
+            .map(|account| account.id.clone());
+        let key = match &account_id {
+            Some(account_id) => account_id.clone(),
+            // The limiter only keeps a hash of the token
+            None => match bearer_token(&request) {
+                Some(token) => format!("token:{}", hex::encode(Sha256::digest(token))),
+                None => self.inner.client_id.clone(),
+            },
+        };
+        let limiter = self
+            .inner
+            .rate_limiter
+            .as_deref()
+            .map(|rate_limiter| (rate_limiter, key.as_str()));
+
+        let future =
+            self.inner
+                .retry_policy
+                .execute(&self.inner.http, request, idempotent, limiter);
+
+        #[cfg(feature = "tracing")]
+        let span = telemetry::request_span(sent.method(), sent.url(), account_id.as_deref());
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument(future, span.clone());
+
+        #[cfg(any(feature = "tracing", feature = "metrics"))]
+        let started = std::time::Instant::now();
+        let (result, _retries) = future.await;
+
+        #[cfg(feature = "tracing")]
+        telemetry::record_outcome(&span, sent.url(), &result, _retries, started.elapsed());
+        #[cfg(feature = "metrics")]
+        crate::metrics::record(sent.method(), sent.url(), &result, started.elapsed());
+
+        if let Ok(response) = &result {
+            if let Some(rate_limit) = RateLimit::from_headers(response.headers()) {
+                *lock(&self.inner.rate_limit) = Some(rate_limit);
+            }
+        }
+        (sent, result)
+    }
+}
+
+// The access token a request is authenticated with
+fn bearer_token(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.to_string())
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{app, header, ok, parse, respond, serve};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn tokens_revoke_themselves() {
+        let (addr, received) = serve(vec![ok(&json!({ "success": true }))]);
+        app(addr).await.revoke_token("TOKEN").await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(parse(&received[0]).0, "POST /oauth/revoke HTTP/1.1");
+        assert_eq!(header(&received[0], "authorization"), Some("Bearer TOKEN"));
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_revocations_are_errors() {
+        let (addr, _) = serve(vec![
+            ok(&json!({ "success": false })),
+            respond("401 Unauthorized", "{}"),
+        ]);
+        let app = app(addr).await;
+        assert_eq!(
+            app.revoke_token("TOKEN").await,
+            Err("Nylas did not confirm the revocation.".to_string())
+        );
+        assert_eq!(
+            app.revoke_token("TOKEN").await,
+            Err("Request failed with status: 401 Unauthorized".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn revoking_all_tokens_may_keep_one() {
+        let (addr, received) = serve(vec![
+            ok(&json!({ "success": true })),
+            ok(&json!({ "success": true })),
+            ok(&json!({ "success": false })),
+        ]);
+        let app = app(addr).await;
+        app.revoke_all_tokens("a1", Some("TOKEN")).await.unwrap();
+        app.revoke_all_tokens("a1", None).await.unwrap();
+        assert!(app.revoke_all_tokens("a1", None).await.is_err());
+
+        let received = received.lock().unwrap();
+        assert_eq!(
+            parse(&received[0]),
+            (
+                "POST /a/CLIENT_ID/accounts/a1/revoke-all HTTP/1.1",
+                json!({ "keep_access_token": "TOKEN" })
+            )
+        );
+        assert_eq!(
+            header(&received[0], "authorization"),
+            Some("Basic Q0xJRU5UX1NFQ1JFVDo=")
+        );
+        assert_eq!(parse(&received[1]).1, json!({}));
+    }
+
+    #[tokio::test]
+    async fn token_info_posts_the_token() {
+        let (addr, received) = serve(vec![ok(&json!({
+            "scopes": "email,calendar", "state": "valid",
+            "created_at": 1704067200, "updated_at": 1704067200
+        }))]);
+        let info = app(addr).await.token_info("a1", "TOKEN").await.unwrap();
+        assert!(info.is_valid());
+        assert_eq!(info.scopes(), vec!["email", "calendar"]);
+
+        let received = received.lock().unwrap();
+        assert_eq!(
+            parse(&received[0]),
+            (
+                "POST /a/CLIENT_ID/accounts/a1/token-info HTTP/1.1",
+                json!({ "access_token": "TOKEN" })
+            )
+        );
+    }
 }
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// Represents an email address of a contact.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContactEmail {
+    pub email: String,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+/// Represents a phone number of a contact.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PhoneNumber {
+    pub number: String,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+/// Represents a contact from the address book of the account.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Contact {
+    pub id: String,
+    pub object: String,
+    pub account_id: String,
+    pub given_name: Option<String>,
+    pub middle_name: Option<String>,
+    pub surname: Option<String>,
+    pub suffix: Option<String>,
+    pub nickname: Option<String>,
+    pub company_name: Option<String>,
+    pub job_title: Option<String>,
+    pub notes: Option<String>,
+    pub birthday: Option<String>,
+    pub picture_url: Option<String>,
+    pub source: Option<String>,
+    #[serde(default)]
+    pub emails: Vec<ContactEmail>,
+    #[serde(default)]
+    pub phone_numbers: Vec<PhoneNumber>,
+}
@@ -0,0 +1,732 @@
+use crate::client::Nylas;
+use crate::contacts::Contact;
+use crate::messages::{Event, File, Folder, Label, Message, View};
+use crate::threads::Thread;
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt;
+use url::Url;
+
+/// Represents the type of an object reported by the delta endpoints.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ObjectType {
+    Message,
+    Draft,
+    Thread,
+    Event,
+    Calendar,
+    Contact,
+    File,
+    Folder,
+    Label,
+    Other(String),
+}
+
+impl From<&str> for ObjectType {
+    fn from(object: &str) -> Self {
+        match object {
+            "message" => ObjectType::Message,
+            "draft" => ObjectType::Draft,
+            "thread" => ObjectType::Thread,
+            "event" => ObjectType::Event,
+            "calendar" => ObjectType::Calendar,
+            "contact" => ObjectType::Contact,
+            "file" => ObjectType::File,
+            "folder" => ObjectType::Folder,
+            "label" => ObjectType::Label,
+            other => ObjectType::Other(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjectType::Message => write!(f, "message"),
+            ObjectType::Draft => write!(f, "draft"),
+            ObjectType::Thread => write!(f, "thread"),
+            ObjectType::Event => write!(f, "event"),
+            ObjectType::Calendar => write!(f, "calendar"),
+            ObjectType::Contact => write!(f, "contact"),
+            ObjectType::File => write!(f, "file"),
+            ObjectType::Folder => write!(f, "folder"),
+            ObjectType::Label => write!(f, "label"),
+            ObjectType::Other(object) => write!(f, "{}", object),
+        }
+    }
+}
+
+/// Represents the object carried by a `create` or `modify` delta.
+#[derive(Debug, Clone)]
+pub enum DeltaObject {
+    Message(Box<Message>),
+    Draft(Box<Message>),
+    Thread(Box<Thread>),
+    Event(Box<Event>),
+    Contact(Box<Contact>),
+    File(File),
+    Folder(Folder),
+    Label(Label),
+    /// An object without a dedicated type in the SDK, such as calendars.
+    Other(ObjectType, Value),
+}
+
+impl DeltaObject {
+    /// The type of the object.
+    pub fn object_type(&self) -> ObjectType {
+        match self {
+            DeltaObject::Message(_) => ObjectType::Message,
+            DeltaObject::Draft(_) => ObjectType::Draft,
+            DeltaObject::Thread(_) => ObjectType::Thread,
+            DeltaObject::Event(_) => ObjectType::Event,
+            DeltaObject::Contact(_) => ObjectType::Contact,
+            DeltaObject::File(_) => ObjectType::File,
+            DeltaObject::Folder(_) => ObjectType::Folder,
+            DeltaObject::Label(_) => ObjectType::Label,
+            DeltaObject::Other(object_type, _) => object_type.clone(),
+        }
+    }
+
+    pub(crate) fn parse(
+        object_type: ObjectType,
+        attributes: Value,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(match object_type {
+            ObjectType::Message => DeltaObject::Message(serde_json::from_value(attributes)?),
+            ObjectType::Draft => DeltaObject::Draft(serde_json::from_value(attributes)?),
+            ObjectType::Thread => DeltaObject::Thread(serde_json::from_value(attributes)?),
+            ObjectType::Event => DeltaObject::Event(serde_json::from_value(attributes)?),
+            ObjectType::Contact => DeltaObject::Contact(serde_json::from_value(attributes)?),
+            ObjectType::File => DeltaObject::File(serde_json::from_value(attributes)?),
+            ObjectType::Folder => DeltaObject::Folder(serde_json::from_value(attributes)?),
+            ObjectType::Label => DeltaObject::Label(serde_json::from_value(attributes)?),
+            other => DeltaObject::Other(other, attributes),
+        })
+    }
+}
+
+/// Represents a single change of the account, as reported by the delta endpoints.
+#[derive(Debug, Clone)]
+pub enum Delta {
+    /// An object was created.
+    Create { cursor: String, object: DeltaObject },
+    /// An object was modified; the delta carries its new state.
+    Modify { cursor: String, object: DeltaObject },
+    /// An object was deleted.
+    Delete {
+        cursor: String,
+        object_type: ObjectType,
+        id: String,
+    },
+    /// A delta the SDK can't read, e.g. an unknown event or an object which doesn't match its
+    /// type. It is kept as sent by Nylas so that following the changes moves past it.
+    Unknown {
+        cursor: String,
+        raw: Value,
+        /// Why the delta couldn't be read.
+        error: String,
+    },
+}
+
+impl Delta {
+    /// The cursor pointing right after this delta.
+    pub fn cursor(&self) -> &str {
+        match self {
+            Delta::Create { cursor, .. }
+            | Delta::Modify { cursor, .. }
+            | Delta::Delete { cursor, .. }
+            | Delta::Unknown { cursor, .. } => cursor,
+        }
+    }
+
+    /// The type of the changed object.
+    pub fn object_type(&self) -> ObjectType {
+        match self {
+            Delta::Create { object, .. } | Delta::Modify { object, .. } => object.object_type(),
+            Delta::Delete { object_type, .. } => object_type.clone(),
+            Delta::Unknown { raw, .. } => ObjectType::from(
+                raw.get("object")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+impl From<Value> for Delta {
+    fn from(raw: Value) -> Self {
+        let delta = serde_json::from_value::<RawDelta>(raw.clone())
+            .map_err(|e| format!("JSON Parsing Error: {:?}", e))
+            .and_then(Delta::try_from);
+        match delta {
+            Ok(delta) => delta,
+            Err(error) => Delta::Unknown {
+                cursor: raw
+                    .get("cursor")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                raw,
+                error,
+            },
+        }
+    }
+}
+
+/// The raw delta, as sent by Nylas.
+#[derive(Debug, Deserialize)]
+struct RawDelta {
+    cursor: String,
+    event: String,
+    id: String,
+    object: String,
+    attributes: Option<Value>,
+}
+
+impl TryFrom<RawDelta> for Delta {
+    type Error = String;
+
+    fn try_from(raw: RawDelta) -> Result<Self, Self::Error> {
+        let object_type = ObjectType::from(raw.object.as_str());
+
+        if raw.event == "delete" {
+            return Ok(Delta::Delete {
+                cursor: raw.cursor,
+                object_type,
+                id: raw.id,
+            });
+        }
+
+        let attributes = raw
+            .attributes
+            .ok_or_else(|| format!("Missing attributes for {} {}.", raw.object, raw.id))?;
+        let object = DeltaObject::parse(object_type, attributes)
+            .map_err(|e| format!("Unable to parse {} {}: {:?}", raw.object, raw.id, e))?;
+
+        match raw.event.as_str() {
+            "create" => Ok(Delta::Create {
+                cursor: raw.cursor,
+                object,
+            }),
+            "modify" => Ok(Delta::Modify {
+                cursor: raw.cursor,
+                object,
+            }),
+            other => Err(format!("Unknown delta event: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawDeltaPage {
+    cursor_start: String,
+    cursor_end: String,
+    #[serde(default)]
+    deltas: Vec<Value>,
+}
+
+/// Represents a page of deltas.
+#[derive(Debug, Clone)]
+pub struct DeltaPage {
+    /// The cursor the page was requested with.
+    pub cursor_start: String,
+    /// The cursor to request the next page with.
+    pub cursor_end: String,
+    pub deltas: Vec<Delta>,
+}
+
+// Deltas which can't be read are kept as `Delta::Unknown`, a page never fails because of one
+impl From<RawDeltaPage> for DeltaPage {
+    fn from(raw: RawDeltaPage) -> Self {
+        DeltaPage {
+            cursor_start: raw.cursor_start,
+            cursor_end: raw.cursor_end,
+            deltas: raw.deltas.into_iter().map(Delta::from).collect(),
+        }
+    }
+}
+
+/// Options applied to the delta requests.
+///
+/// `include_types` and `exclude_types` are mutually exclusive.
+#[derive(Debug, Clone, Default)]
+pub struct DeltaOptions {
+    /// Only return deltas for these object types.
+    pub include_types: Vec<ObjectType>,
+    /// Return deltas for every object type except these.
+    pub exclude_types: Vec<ObjectType>,
+    /// Use `View::Expanded` to receive expanded messages and threads.
+    pub view: Option<View>,
+}
+
+/// Struct for following the changes of an account with the Nylas delta endpoints.
+pub struct Deltas<'a> {
+    pub nylas: &'a Nylas,
+}
+
+impl<'a> Deltas<'a> {
+    pub fn new(nylas: &'a Nylas) -> Self {
+        Deltas { nylas }
+    }
+
+    /// Retrieve the cursor pointing to the current state of the account.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the cursor if successful, or an error message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use nylas::client::Nylas;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client_id = "YOUR_CLIENT_ID";
+    ///     let client_secret = "YOUR_CLIENT_SECRET";
+    ///     let access_token = "YOUR_ACCESS_TOKEN";
+    ///
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///
+    ///     let cursor = nylas.deltas().latest_cursor().await.unwrap();
+    /// }
+    /// ```
+    pub async fn latest_cursor(&self) -> Result<String, String> {
+        let url = "https://api.nylas.com/delta/latest_cursor";
+
+        #[derive(Deserialize)]
+        struct LatestCursor {
+            cursor: String,
+        }
+
+        // Fetching the latest cursor has no side effect
+        let request = self
+            .nylas
+            .send_idempotent(self.nylas.request(Method::POST, url).await?);
+
+        // Handle the HTTP response
+        match request.await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    let latest: LatestCursor = response
+                        .json()
+                        .await
+                        .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
+                    Ok(latest.cursor)
+                } else {
+                    Err(format!("Request failed with status: {}", response.status()))
+                }
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// Retrieve a single page of deltas following the given cursor.
+    ///
+    /// # Arguments
+    ///
+    /// - `cursor`: The cursor to start from.
+    /// - `options`: The object types to include or exclude and the view.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the page if successful, or an error message. The page is the last
+    /// one when its `cursor_end` equals its `cursor_start`.
+    pub async fn page(&self, cursor: &str, options: &DeltaOptions) -> Result<DeltaPage, String> {
+        let url = self.url("https://api.nylas.com/delta", cursor, options, None)?;
+        let response = self.send(url).await?;
+        let raw: RawDeltaPage = response
+            .json()
+            .await
+            .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
+        Ok(DeltaPage::from(raw))
+    }
+
+    /// Retrieve every delta following the given cursor, going through all pages.
+    ///
+    /// # Arguments
+    ///
+    /// - `cursor`: The cursor to start from.
+    /// - `options`: The object types to include or exclude and the view.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deltas, in order, and the cursor to resume from.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use nylas::client::Nylas;
+    /// use nylas::deltas::{Delta, DeltaOptions, ObjectType};
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let access_token = "YOUR_ACCESS_TOKEN";
+    ///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", Some(access_token))
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     let cursor = "CURSOR_SAVED_LAST_TIME";
+    ///     let options = DeltaOptions {
+    ///         include_types: vec![ObjectType::Message, ObjectType::Thread],
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     let (deltas, next_cursor) = nylas.deltas().since(cursor, &options).await.unwrap();
+    ///     for delta in deltas {
+    ///         match delta {
+    ///             Delta::Create { object, .. } => println!("created {:?}", object.object_type()),
+    ///             Delta::Modify { object, .. } => println!("modified {:?}", object.object_type()),
+    ///             Delta::Delete { id, .. } => println!("deleted {}", id),
+    ///             Delta::Unknown { error, .. } => eprintln!("skipped: {}", error),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn since(
+        &self,
+        cursor: &str,
+        options: &DeltaOptions,
+    ) -> Result<(Vec<Delta>, String), String> {
+        let mut deltas = Vec::new();
+        let mut cursor = cursor.to_string();
+
+        loop {
+            let page = self.page(&cursor, options).await?;
+            let done = page.deltas.is_empty() || page.cursor_end == page.cursor_start;
+            deltas.extend(page.deltas);
+            cursor = page.cursor_end;
+            if done {
+                break;
+            }
+        }
+
+        Ok((deltas, cursor))
+    }
+
+    /// Wait for changes following the given cursor.
+    ///
+    /// The request is held open by Nylas until changes are available or `timeout` seconds have
+    /// elapsed, in which case the returned page is empty.
+    ///
+    /// # Arguments
+    ///
+    /// - `cursor`: The cursor to start from.
+    /// - `timeout`: The maximum number of seconds to wait for changes.
+    /// - `options`: The object types to include or exclude and the view.
+    pub async fn longpoll(
+        &self,
+        cursor: &str,
+        timeout: u64,
+        options: &DeltaOptions,
+    ) -> Result<DeltaPage, String> {
+        let url = self.url(
+            "https://api.nylas.com/delta/longpoll",
+            cursor,
+            options,
+            Some(timeout),
+        )?;
+        let response = self.send(url).await?;
+        let raw: RawDeltaPage = response
+            .json()
+            .await
+            .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
+        Ok(DeltaPage::from(raw))
+    }
+
+    /// Stream changes following the given cursor as they happen.
+    ///
+    /// The connection is kept open and `on_delta` is called for each delta, in order, until it
+    /// returns `false` or the connection is closed by Nylas.
+    ///
+    /// # Arguments
+    ///
+    /// - `cursor`: The cursor to start from.
+    /// - `options`: The object types to include or exclude and the view.
+    /// - `on_delta`: The callback receiving each delta; return `false` to stop streaming.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the cursor of the last delta received, to resume from.
+    pub async fn stream<F>(
+        &self,
+        cursor: &str,
+        options: &DeltaOptions,
+        mut on_delta: F,
+    ) -> Result<String, String>
+    where
+        F: FnMut(Delta) -> bool,
+    {
+        let url = self.url(
+            "https://api.nylas.com/delta/streaming",
+            cursor,
+            options,
+            None,
+        )?;
+        let mut response = self.send(url).await?;
+
+        let mut cursor = cursor.to_string();
+        let mut buffer: Vec<u8> = Vec::new();
+
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            buffer.extend_from_slice(&chunk);
+            if !Self::consume(&mut buffer, &mut cursor, &mut on_delta)? {
+                break;
+            }
+        }
+
+        Ok(cursor)
+    }
+
+    // Hand the complete JSON values of the buffer to the callback, keeping a trailing partial one
+    // for later. Returns whether to go on streaming.
+    fn consume<F>(
+        buffer: &mut Vec<u8>,
+        cursor: &mut String,
+        on_delta: &mut F,
+    ) -> Result<bool, String>
+    where
+        F: FnMut(Delta) -> bool,
+    {
+        let mut consumed = 0;
+        let mut values = serde_json::Deserializer::from_slice(buffer).into_iter::<Value>();
+        loop {
+            match values.next() {
+                Some(Ok(value)) => {
+                    consumed = values.byte_offset();
+                    for delta in Self::stream_deltas(value)? {
+                        // Deltas too malformed to have a cursor can't be resumed from
+                        if !delta.cursor().is_empty() {
+                            *cursor = delta.cursor().to_string();
+                        }
+                        if !on_delta(delta) {
+                            return Ok(false);
+                        }
+                    }
+                }
+                Some(Err(e)) if e.is_eof() => break,
+                Some(Err(e)) => return Err(format!("JSON Parsing Error: {:?}", e)),
+                None => break,
+            }
+        }
+        buffer.drain(..consumed);
+        Ok(true)
+    }
+
+    // The streaming endpoint may send single deltas or pages of deltas.
+    fn stream_deltas(value: Value) -> Result<Vec<Delta>, String> {
+        if value.get("deltas").is_some() {
+            let raw: RawDeltaPage = serde_json::from_value(value)
+                .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
+            Ok(DeltaPage::from(raw).deltas)
+        } else {
+            Ok(vec![Delta::from(value)])
+        }
+    }
+
+    fn url(
+        &self,
+        base_url: &str,
+        cursor: &str,
+        options: &DeltaOptions,
+        timeout: Option<u64>,
+    ) -> Result<Url, String> {
+        if !options.include_types.is_empty() && !options.exclude_types.is_empty() {
+            return Err("include_types and exclude_types can't be used together.".to_string());
+        }
+
+        let mut url = Url::parse(base_url).map_err(|e| e.to_string())?;
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("cursor", cursor);
+
+            let join = |types: &[ObjectType]| {
+                types
+                    .iter()
+                    .map(|object_type| object_type.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            };
+            if !options.include_types.is_empty() {
+                query.append_pair("include_types", &join(&options.include_types));
+            }
+            if !options.exclude_types.is_empty() {
+                query.append_pair("exclude_types", &join(&options.exclude_types));
+            }
+            if let Some(view) = &options.view {
+                query.append_pair("view", &view.to_string());
+            }
+            if let Some(timeout) = timeout {
+                query.append_pair("timeout", &timeout.to_string());
+            }
+        }
+
+        Ok(url)
+    }
+
+    async fn send(&self, url: Url) -> Result<reqwest::Response, String> {
+        let request = self.nylas.send(self.nylas.request(Method::GET, url).await?);
+
+        // Handle the HTTP response
+        match request.await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    Ok(response)
+                } else {
+                    Err(format!("Request failed with status: {}", response.status()))
+                }
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(id: &str) -> Value {
+        json!({
+            "id": id, "object": "message", "account_id": "a1", "thread_id": "t1",
+            "subject": "Hello", "from": [], "to": [], "cc": [], "bcc": [], "reply_to": [],
+            "date": 1700000000, "unread": true, "starred": false, "snippet": "", "body": "",
+            "files": [], "events": [], "folder": null, "labels": [], "headers": null
+        })
+    }
+
+    fn page(deltas: Vec<Value>) -> DeltaPage {
+        let raw: RawDeltaPage = serde_json::from_value(json!({
+            "cursor_start": "c0",
+            "cursor_end": "c3",
+            "deltas": deltas,
+        }))
+        .unwrap();
+        DeltaPage::from(raw)
+    }
+
+    #[test]
+    fn malformed_object_does_not_fail_the_page() {
+        let page = page(vec![
+            json!({"cursor": "c1", "event": "create", "id": "m1", "object": "message",
+                   "attributes": message("m1")}),
+            json!({"cursor": "c2", "event": "modify", "id": "e1", "object": "event",
+                   "attributes": {"id": "e1", "title": 42}}),
+            json!({"cursor": "c3", "event": "delete", "id": "m1", "object": "message"}),
+        ]);
+
+        assert_eq!(page.cursor_end, "c3");
+        assert_eq!(page.deltas.len(), 3);
+        assert!(matches!(
+            &page.deltas[0],
+            Delta::Create {
+                object: DeltaObject::Message(_),
+                ..
+            }
+        ));
+        match &page.deltas[1] {
+            Delta::Unknown { cursor, raw, error } => {
+                assert_eq!(cursor, "c2");
+                assert_eq!(raw["id"], "e1");
+                assert!(error.starts_with("Unable to parse event e1"), "{}", error);
+            }
+            other => panic!("unexpected delta: {:?}", other),
+        }
+        assert_eq!(page.deltas[1].object_type(), ObjectType::Event);
+        assert!(matches!(&page.deltas[2], Delta::Delete { id, .. } if id == "m1"));
+    }
+
+    #[test]
+    fn unknown_event_is_kept() {
+        let delta = Delta::from(json!({
+            "cursor": "c1", "event": "archive", "id": "m1", "object": "message",
+            "attributes": message("m1"),
+        }));
+        match delta {
+            Delta::Unknown { cursor, error, .. } => {
+                assert_eq!(cursor, "c1");
+                assert_eq!(error, "Unknown delta event: archive");
+            }
+            other => panic!("unexpected delta: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn missing_attributes_are_reported() {
+        let delta = Delta::from(json!({
+            "cursor": "c1", "event": "create", "id": "m1", "object": "message",
+        }));
+        assert!(
+            matches!(&delta, Delta::Unknown { error, .. } if error == "Missing attributes for message m1.")
+        );
+    }
+
+    #[test]
+    fn delta_without_cursor_has_an_empty_cursor() {
+        let delta = Delta::from(json!({"unexpected": true}));
+        assert!(matches!(&delta, Delta::Unknown { .. }));
+        assert_eq!(delta.cursor(), "");
+        assert_eq!(delta.object_type(), ObjectType::Other(String::new()));
+    }
+
+    #[test]
+    fn stream_accepts_single_deltas_and_pages() {
+        let single = Deltas::stream_deltas(json!({
+            "cursor": "c1", "event": "delete", "id": "m1", "object": "message",
+        }))
+        .unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].cursor(), "c1");
+
+        let page = Deltas::stream_deltas(json!({
+            "cursor_start": "c1",
+            "cursor_end": "c2",
+            "deltas": [{"cursor": "c2", "event": "modify", "id": "x", "object": "message",
+                        "attributes": {}}],
+        }))
+        .unwrap();
+        assert!(matches!(&page[..], [Delta::Unknown { cursor, .. }] if cursor == "c2"));
+    }
+
+    #[test]
+    fn stream_keeps_the_cursor_of_the_last_delta_with_one() {
+        let mut buffer = br#"{"cursor": "c1", "event": "delete", "id": "m1", "object": "message"}
+            {"unexpected": true} {"cursor": "c2", "ev"#
+            .to_vec();
+        let mut cursor = "c0".to_string();
+        let mut received = 0;
+        let more = Deltas::consume(&mut buffer, &mut cursor, &mut |_| {
+            received += 1;
+            true
+        })
+        .unwrap();
+        assert!(more);
+        assert_eq!(received, 2);
+        assert_eq!(cursor, "c1");
+        assert_eq!(buffer, br#" {"cursor": "c2", "ev"#);
+
+        // A malformed delta stopping the stream leaves the last known cursor
+        let mut buffer = br#"{"unexpected": true}"#.to_vec();
+        let more = Deltas::consume(&mut buffer, &mut cursor, &mut |_| false).unwrap();
+        assert!(!more);
+        assert_eq!(cursor, "c1");
+    }
+
+    #[tokio::test]
+    async fn include_and_exclude_types_are_exclusive() {
+        let options = DeltaOptions {
+            include_types: vec![ObjectType::Message],
+            exclude_types: vec![ObjectType::Thread],
+            view: None,
+        };
+        let nylas = Nylas::new("CLIENT_ID", "CLIENT_SECRET", None)
+            .await
+            .unwrap();
+        let error = Deltas::new(&nylas)
+            .url("https://api.nylas.com/delta", "c0", &options, None)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            "include_types and exclude_types can't be used together."
+        );
+    }
+}
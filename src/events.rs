@@ -0,0 +1,71 @@
+use crate::client::Nylas;
+use crate::messages::Event;
+use reqwest::Method;
+
+/// Struct for working with Nylas calendar events.
+pub struct Events<'a> {
+    pub nylas: &'a Nylas,
+}
+
+impl<'a> Events<'a> {
+    pub fn new(nylas: &'a Nylas) -> Self {
+        Events { nylas }
+    }
+
+    /// Retrieve a specific event by its ID.
+    ///
+    /// # Arguments
+    ///
+    /// - `self`: A reference to the `Events` struct.
+    /// - `id`: The ID of the event you want to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing an `Option<Event>` if successful (Some(event)), or an error message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use nylas::client::Nylas;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client_id = "YOUR_CLIENT_ID";
+    ///     let client_secret = "YOUR_CLIENT_SECRET";
+    ///     let access_token = "YOUR_ACCESS_TOKEN";
+    ///
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///
+    ///     match nylas.events().get("your_event_id_here").await {
+    ///         Ok(Some(event)) => println!("{:?}", event),
+    ///         Ok(None) => println!("Event not found."),
+    ///         Err(err) => eprintln!("Error: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn get(&self, id: &str) -> Result<Option<Event>, String> {
+        // Construct the API URL for the specific event
+        let url = format!("https://api.nylas.com/events/{}", id);
+
+        let request = self
+            .nylas
+            .send(self.nylas.request(Method::GET, &url).await?);
+
+        // Handle the HTTP response
+        match request.await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    let event: Event = response
+                        .json()
+                        .await
+                        .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
+                    Ok(Some(event))
+                } else if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    Ok(None)
+                } else {
+                    Err(format!("Request failed with status: {}", response.status()))
+                }
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+}
@@ -22,7 +22,7 @@
 //!
 //! 3. Initialize the Nylas client by providing your Nylas client ID, client secret, and access_token:
 //!
-//! ```rust
+//! ```rust,no_run
 //! use nylas::client::Nylas;
 //!
 //! #[tokio::main]
@@ -30,7 +30,7 @@
 //!     let client_id = "YOUR_CLIENT_ID";
 //!     let client_secret = "YOUR_CLIENT_SECRET";
 //!     let access_token = "YOUR_ACCESS_TOKEN";
-//!     let mut nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+//!     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
 //!     // ...
 //! }
 //! ```
@@ -42,14 +42,26 @@
 //! The Nylas Rust SDK offers a range of features to simplify your integration with the Nylas Email and Calendar API:
 //!
 //! - **Simplified Authentication**: Quickly generate authentication URLs, easily exchange authorization codes for access tokens, and manage various authentication flows with simplicity.
+//! - **Native Authentication**: Connect IMAP, Exchange, Google and Microsoft 365 accounts from their credentials, without any browser.
 //! - **Account Information Retrieval**: Retrieve comprehensive details about the Nylas account associated with your access token, including account metadata, plan details, and account status.
-//! - **Message Management**: Seamlessly interact with email messages, allowing you to retrieve messages, send emails, filter messages based on criteria, and perform a wide range of message-related actions.
+//! - **Account Management**: List, upgrade, downgrade and delete the accounts connected to your application and read their billing and sync states.
+//! - **Mailbox Organization**: Work with folders and labels through a single provider-agnostic interface to list containers and move, archive or trash messages.
+//! - **Message Management**: Seamlessly interact with email messages, allowing you to retrieve messages, send emails, filter messages based on criteria or Gmail-style queries, and perform a wide range of message-related actions.
+//! - **Delta Sync**: Follow every change of an account with cursors, by pages, long polling or streaming, instead of polling the messages.
+//! - **Local Replica**: Keep a local copy of the messages, threads, folders or labels and contacts of an account up to date, in memory or in your own storage backend.
+//! - **Offline Search**: Search the synced messages by subject, body, participants or attachment names with ranked results, without any request to Nylas.
+//! - **Token Refresh**: Provide the access token at request time, refreshing it before it expires or once it is rejected.
+//! - **Automatic Retries**: Retry connection failures and transient server errors with an exponential backoff and jitter, without ever replaying a non-idempotent request.
+//! - **Rate-Limit Awareness**: Wait for the `Retry-After` delay of rate-limited responses, read the rate-limit headers and throttle requests per account with a token bucket.
+//! - **Shareable Client**: Clone the client cheaply and use it from many tasks at once, every clone sharing the same connection pool, retry policy and rate limiter.
+//! - **Middleware**: Run your own hooks before every request and after every response, e.g. to add request ids and headers or to audit the calls.
+//! - **Webhooks**: Register, pause, resume and delete the webhooks of your application, verify incoming notifications and parse them into typed deltas.
 //!
 //! # Usage
 //!
 //! ## Authentication
 //!
-//! ```rust
+//! ```rust,ignore
 //! // Generate an authentication URL
 //! let auth_url = nylas.authentication_url(redirect_uri, login_hint, state, scopes);
 //!
@@ -60,14 +72,14 @@
 //!
 //! ## Account Information
 //!
-//! ```rust
+//! ```rust,ignore
 //! // Access account details
-//! println!("{:?}", nylas.account);
+//! println!("{:?}", nylas.cached_account());
 //! ```
 //!
 //! ## Message Management
 //!
-//! ```rust
+//! ```rust,ignore
 //! // Retrieve all messages
 //! let messages = nylas.messages().all().await;
 //!
@@ -88,6 +100,15 @@
 //! let message_result = nylas.messages().get(message_id, Some(View::Expanded)).await;
 //! ```
 //!
+//! # Cargo Features
+//!
+//! - `axum`: Webhook receiver for axum, see `adapters::axum`.
+//! - `metrics`: Request counts, latencies, errors and rate-limit hits per endpoint, see `metrics`.
+//! - `rocket`: Webhook receiver for rocket, see `adapters::rocket`.
+//! - `search`: Offline full-text search over synced messages, see `search::SearchIndex`.
+//! - `sqlite`: SQLite storage backend for the sync engine, see `sqlite::SqliteStore`.
+//! - `tracing`: Spans for every request sent to Nylas, with the method, endpoint, status, latency, retries and account, see [tracing](https://docs.rs/tracing).
+//!
 //! # GitHub Repository
 //!
 //! You can access the source code for this library on [GitHub](https://github.com/wiseaidev/nylas).
@@ -97,5 +118,32 @@
 //! We actively welcome contributions and bug reports from the community. If you'd like to contribute, report a bug, or suggest an enhancement, please feel free to engage with the project on [GitHub](https://github.com/wiseaidev/nylas). Your contributions are invaluable in making this library better for everyone.
 
 pub mod accounts;
+#[cfg(any(feature = "axum", feature = "rocket"))]
+pub mod adapters;
+pub mod auth;
 pub mod client;
+pub mod contacts;
+pub mod deltas;
+pub mod events;
+pub mod mailbox;
 pub mod messages;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod middleware;
+pub mod native_auth;
+pub mod query;
+pub mod rate_limit;
+pub mod retry;
+#[cfg(feature = "search")]
+pub mod search;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod sync;
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+mod telemetry;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod threads;
+pub mod timestamp;
+pub mod token;
+pub mod webhooks;
@@ -0,0 +1,477 @@
+use crate::accounts::OrganizationUnit;
+use crate::client::Nylas;
+use crate::messages::{Folder, Label, Message};
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::fmt;
+
+/// Represents a system role of a folder or label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Role {
+    Inbox,
+    All,
+    Archive,
+    Drafts,
+    Important,
+    Sent,
+    Spam,
+    Trash,
+    Other(String),
+}
+
+impl From<&str> for Role {
+    fn from(name: &str) -> Self {
+        match name {
+            "inbox" => Role::Inbox,
+            "all" => Role::All,
+            "archive" => Role::Archive,
+            "drafts" => Role::Drafts,
+            "important" => Role::Important,
+            "sent" => Role::Sent,
+            "spam" => Role::Spam,
+            "trash" => Role::Trash,
+            other => Role::Other(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Inbox => write!(f, "inbox"),
+            Role::All => write!(f, "all"),
+            Role::Archive => write!(f, "archive"),
+            Role::Drafts => write!(f, "drafts"),
+            Role::Important => write!(f, "important"),
+            Role::Sent => write!(f, "sent"),
+            Role::Spam => write!(f, "spam"),
+            Role::Trash => write!(f, "trash"),
+            Role::Other(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Represents a message container, either a folder or a label depending on the account.
+#[derive(Debug, Clone)]
+pub enum Container {
+    Folder(Folder),
+    Label(Label),
+}
+
+impl Container {
+    /// The ID of the folder or label.
+    pub fn id(&self) -> &str {
+        match self {
+            Container::Folder(folder) => &folder.id,
+            Container::Label(label) => &label.id,
+        }
+    }
+
+    /// The human readable name of the folder or label.
+    pub fn display_name(&self) -> &str {
+        match self {
+            Container::Folder(folder) => &folder.display_name,
+            Container::Label(label) => &label.display_name,
+        }
+    }
+
+    /// The system role of the folder or label, or `None` for user-created containers.
+    pub fn role(&self) -> Option<Role> {
+        match self {
+            Container::Folder(folder) => folder.name.as_deref().map(Role::from),
+            Container::Label(label) if label.name.is_empty() => None,
+            Container::Label(label) => Some(Role::from(label.name.as_str())),
+        }
+    }
+}
+
+/// Struct for working with folders and labels without caring which one the account uses.
+///
+/// Every operation is dispatched on the `organization_unit` of the connected account: folder
+/// based accounts (IMAP, Exchange, ...) use the `/folders` endpoint and `folder_id`, while label
+/// based accounts (Gmail) use the `/labels` endpoint and `label_ids`.
+pub struct Mailbox<'a> {
+    pub nylas: &'a Nylas,
+}
+
+impl<'a> Mailbox<'a> {
+    pub fn new(nylas: &'a Nylas) -> Self {
+        Mailbox { nylas }
+    }
+
+    /// Returns the organization unit of the connected account, fetching the account details
+    /// first if they have not been loaded yet.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the account details can't be retrieved or if the
+    /// account reports an unknown organization unit.
+    pub async fn organization_unit(&self) -> Result<OrganizationUnit, String> {
+        let account = match self.nylas.cached_account() {
+            Some(account) => account,
+            None => self.nylas.account().await?,
+        };
+        match account.organization_unit {
+            OrganizationUnit::Unknown(name) => Err(unknown(&name)),
+            organization_unit => Ok(organization_unit),
+        }
+    }
+
+    /// Retrieve all folders or labels of the connected account.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of containers if successful, or an error message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use nylas::client::Nylas;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client_id = "YOUR_CLIENT_ID";
+    ///     let client_secret = "YOUR_CLIENT_SECRET";
+    ///     let access_token = "YOUR_ACCESS_TOKEN";
+    ///
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///
+    ///     match nylas.mailbox().containers().await {
+    ///         Ok(containers) => {
+    ///             for container in containers {
+    ///                 println!("{}: {:?}", container.display_name(), container.role());
+    ///             }
+    ///         }
+    ///         Err(err) => eprintln!("Error: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn containers(&self) -> Result<Vec<Container>, String> {
+        let organization_unit = self.organization_unit().await?;
+
+        let url = match &organization_unit {
+            OrganizationUnit::Folder => "https://api.nylas.com/folders",
+            OrganizationUnit::Label => "https://api.nylas.com/labels",
+            OrganizationUnit::Unknown(name) => return Err(unknown(name)),
+        };
+
+        let request = self.nylas.send(self.nylas.request(Method::GET, url).await?);
+
+        // Handle the HTTP response
+        match request.await {
+            Ok(response) => {
+                if !response.status().is_success() {
+                    return Err(format!("Request failed with status: {}", response.status()));
+                }
+
+                match organization_unit {
+                    OrganizationUnit::Folder => {
+                        let folders: Vec<Folder> = response
+                            .json()
+                            .await
+                            .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
+                        Ok(folders.into_iter().map(Container::Folder).collect())
+                    }
+                    OrganizationUnit::Label => {
+                        let labels: Vec<Label> = response
+                            .json()
+                            .await
+                            .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
+                        Ok(labels.into_iter().map(Container::Label).collect())
+                    }
+                    OrganizationUnit::Unknown(name) => Err(unknown(&name)),
+                }
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// Find the folder or label holding the given system role.
+    ///
+    /// # Arguments
+    ///
+    /// - `role`: The system role to look for.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `Some(container)` if the account has a container with this role,
+    /// `None` otherwise, or an error message.
+    pub async fn find_by_role(&self, role: Role) -> Result<Option<Container>, String> {
+        let containers = self.containers().await?;
+        Ok(containers
+            .into_iter()
+            .find(|container| container.role().as_ref() == Some(&role)))
+    }
+
+    /// Move a message to the given folder or label.
+    ///
+    /// For label based accounts the message keeps its other labels, except for `inbox` which is
+    /// removed so that the message leaves the inbox the same way it does on folder based accounts.
+    ///
+    /// # Arguments
+    ///
+    /// - `message_id`: The ID of the message to move.
+    /// - `container_id`: The ID of the destination folder or label.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated message if successful, or an error message.
+    pub async fn move_to(&self, message_id: &str, container_id: &str) -> Result<Message, String> {
+        match self.organization_unit().await? {
+            OrganizationUnit::Folder => {
+                self.update_message(message_id, json!({ "folder_id": container_id }))
+                    .await
+            }
+            OrganizationUnit::Label => {
+                let message = self.message(message_id).await?;
+                let mut label_ids: Vec<String> = message
+                    .labels
+                    .iter()
+                    .filter(|label| Role::from(label.name.as_str()) != Role::Inbox)
+                    .map(|label| label.id.clone())
+                    .collect();
+                if !label_ids.iter().any(|id| id == container_id) {
+                    label_ids.push(container_id.to_string());
+                }
+                self.update_message(message_id, json!({ "label_ids": label_ids }))
+                    .await
+            }
+            OrganizationUnit::Unknown(name) => Err(unknown(&name)),
+        }
+    }
+
+    /// Archive a message.
+    ///
+    /// On folder based accounts the message is moved to the `archive` folder. On label based
+    /// accounts the `inbox` label is removed from the message.
+    ///
+    /// # Arguments
+    ///
+    /// - `message_id`: The ID of the message to archive.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated message if successful, or an error message.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if a folder based account has no `archive` folder.
+    pub async fn archive(&self, message_id: &str) -> Result<Message, String> {
+        match self.organization_unit().await? {
+            OrganizationUnit::Folder => {
+                let archive = self
+                    .find_by_role(Role::Archive)
+                    .await?
+                    .ok_or("No archive folder found for this account.")?;
+                self.move_to(message_id, archive.id()).await
+            }
+            OrganizationUnit::Label => {
+                let message = self.message(message_id).await?;
+                let label_ids: Vec<String> = message
+                    .labels
+                    .iter()
+                    .filter(|label| Role::from(label.name.as_str()) != Role::Inbox)
+                    .map(|label| label.id.clone())
+                    .collect();
+                self.update_message(message_id, json!({ "label_ids": label_ids }))
+                    .await
+            }
+            OrganizationUnit::Unknown(name) => Err(unknown(&name)),
+        }
+    }
+
+    /// Move a message to the trash.
+    ///
+    /// # Arguments
+    ///
+    /// - `message_id`: The ID of the message to trash.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated message if successful, or an error message.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the account has no `trash` folder or label.
+    pub async fn trash(&self, message_id: &str) -> Result<Message, String> {
+        let trash = self
+            .find_by_role(Role::Trash)
+            .await?
+            .ok_or("No trash folder or label found for this account.")?;
+        self.move_to(message_id, trash.id()).await
+    }
+
+    async fn message(&self, message_id: &str) -> Result<Message, String> {
+        self.nylas
+            .messages()
+            .get(message_id, None)
+            .await?
+            .ok_or_else(|| format!("Message {} not found.", message_id))
+    }
+
+    async fn update_message(&self, message_id: &str, body: Value) -> Result<Message, String> {
+        let url = format!("https://api.nylas.com/messages/{}", message_id);
+
+        let request = self
+            .nylas
+            .send(self.nylas.request(Method::PUT, &url).await?.json(&body));
+
+        // Handle the HTTP response
+        match request.await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    response
+                        .json()
+                        .await
+                        .map_err(|e| format!("JSON Parsing Error: {:?}", e))
+                } else {
+                    Err(format!("Request failed with status: {}", response.status()))
+                }
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+}
+
+fn unknown(organization_unit: &str) -> String {
+    format!("Unknown organization unit: {}", organization_unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, ok, parse, serve};
+    use std::net::SocketAddr;
+
+    // Answer every request with the next body
+    fn serve_json(bodies: Vec<Value>) -> (SocketAddr, test_support::Received) {
+        serve(bodies.iter().map(ok).collect())
+    }
+
+    async fn nylas(addr: SocketAddr) -> Nylas {
+        test_support::app(addr).await.with_access_token("TOKEN")
+    }
+
+    fn account(organization_unit: &str) -> Value {
+        json!({ "id": "a1", "organization_unit": organization_unit })
+    }
+
+    fn folder(id: &str, name: Option<&str>) -> Value {
+        json!({ "id": id, "display_name": id, "name": name })
+    }
+
+    fn label(id: &str, name: &str) -> Value {
+        json!({ "id": id, "display_name": id, "name": name })
+    }
+
+    fn message(labels: Vec<Value>) -> Value {
+        json!({
+            "id": "m1", "object": "message", "account_id": "a1", "thread_id": "t1",
+            "subject": "Hello", "from": [], "to": [], "cc": [], "bcc": [], "reply_to": [],
+            "date": 1602623196, "unread": false, "starred": false, "snippet": "", "body": "",
+            "files": [], "events": [], "folder": null, "labels": labels, "headers": null
+        })
+    }
+
+    #[tokio::test]
+    async fn folder_accounts_move_messages_with_the_folder_id() {
+        let (addr, received) = serve_json(vec![account("folder"), message(vec![])]);
+        let nylas = nylas(addr).await;
+        nylas.mailbox().move_to("m1", "f1").await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(
+            parse(&received[1]),
+            ("PUT /messages/m1 HTTP/1.1", json!({ "folder_id": "f1" }))
+        );
+    }
+
+    #[tokio::test]
+    async fn label_accounts_move_messages_out_of_the_inbox() {
+        let labels = vec![label("l1", "inbox"), label("l2", "")];
+        let (addr, received) = serve_json(vec![account("label"), message(labels), message(vec![])]);
+        let nylas = nylas(addr).await;
+        nylas.mailbox().move_to("m1", "l3").await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        assert_eq!(parse(&received[1]).0, "GET /messages/m1 HTTP/1.1");
+        assert_eq!(
+            parse(&received[2]),
+            (
+                "PUT /messages/m1 HTTP/1.1",
+                json!({ "label_ids": ["l2", "l3"] })
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn folder_accounts_archive_to_the_archive_folder() {
+        let folders = json!([folder("f1", Some("inbox")), folder("f2", Some("archive"))]);
+        let (addr, received) = serve_json(vec![account("folder"), folders, message(vec![])]);
+        let nylas = nylas(addr).await;
+        nylas.mailbox().archive("m1").await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        assert_eq!(parse(&received[1]).0, "GET /folders HTTP/1.1");
+        assert_eq!(
+            parse(&received[2]),
+            ("PUT /messages/m1 HTTP/1.1", json!({ "folder_id": "f2" }))
+        );
+    }
+
+    #[tokio::test]
+    async fn label_accounts_archive_by_removing_the_inbox_label() {
+        let labels = vec![label("l1", "inbox"), label("l2", "important")];
+        let (addr, received) = serve_json(vec![account("label"), message(labels), message(vec![])]);
+        let nylas = nylas(addr).await;
+        nylas.mailbox().archive("m1").await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        assert_eq!(
+            parse(&received[2]),
+            ("PUT /messages/m1 HTTP/1.1", json!({ "label_ids": ["l2"] }))
+        );
+    }
+
+    #[tokio::test]
+    async fn label_accounts_trash_with_the_trash_label() {
+        let labels = json!([label("l1", "inbox"), label("l9", "trash")]);
+        let (addr, received) = serve_json(vec![
+            account("label"),
+            labels,
+            message(vec![label("l1", "inbox")]),
+            message(vec![]),
+        ]);
+        let nylas = nylas(addr).await;
+        nylas.mailbox().trash("m1").await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 4);
+        assert_eq!(parse(&received[1]).0, "GET /labels HTTP/1.1");
+        assert_eq!(
+            parse(&received[3]),
+            ("PUT /messages/m1 HTTP/1.1", json!({ "label_ids": ["l9"] }))
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_roles_are_errors() {
+        let folders = json!([folder("f1", Some("inbox")), folder("f2", None)]);
+        let (addr, received) = serve_json(vec![account("folder"), folders.clone(), folders]);
+        let nylas = nylas(addr).await;
+        assert_eq!(
+            nylas.mailbox().archive("m1").await.unwrap_err(),
+            "No archive folder found for this account."
+        );
+        assert_eq!(
+            nylas.mailbox().trash("m1").await.unwrap_err(),
+            "No trash folder or label found for this account."
+        );
+
+        // Nothing is sent after the folders are listed
+        assert_eq!(received.lock().unwrap().len(), 3);
+    }
+}
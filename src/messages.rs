@@ -1,23 +1,26 @@
 use crate::client::Nylas;
-use reqwest;
+use crate::query::Query;
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
+use url::Url;
 
 /// Represents an email view.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum View {
     Ids,
     Count,
     Expanded,
 }
 
-impl ToString for View {
-    fn to_string(&self) -> String {
+impl fmt::Display for View {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            View::Ids => "ids".to_string(),
-            View::Count => "count".to_string(),
-            View::Expanded => "expanded".to_string(),
+            View::Ids => write!(f, "ids"),
+            View::Count => write!(f, "count"),
+            View::Expanded => write!(f, "expanded"),
         }
     }
 }
@@ -40,22 +43,46 @@ pub struct File {
 }
 
 /// Represents an event, such as a calendar event.
+///
+/// The optional fields are `null` or missing on many events, e.g. events without a location.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::messages::Event;
+///
+/// let event: Event = serde_json::from_str(r#"{
+///     "account_id": "a1", "busy": true, "calendar_id": "c1", "description": null, "id": "e1",
+///     "location": null, "message_id": null, "object": "event", "owner": null,
+///     "participants": [], "read_only": false, "reminders": null, "status": "confirmed",
+///     "title": "Standup", "visibility": null, "when": {"start_time": 1700000000}
+/// }"#).unwrap();
+/// assert_eq!(event.title.as_deref(), Some("Standup"));
+/// assert_eq!(event.location, None);
+/// ```
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Event {
     pub account_id: String,
     pub busy: bool,
     pub calendar_id: String,
-    pub description: String,
+    #[serde(default)]
+    pub description: Option<String>,
     pub id: String,
-    pub location: String,
-    pub message_id: String,
+    #[serde(default)]
+    pub location: Option<String>,
+    /// The message the event was created from, if any.
+    #[serde(default)]
+    pub message_id: Option<String>,
     pub object: String,
-    pub owner: String,
+    #[serde(default)]
+    pub owner: Option<String>,
     pub participants: Vec<Participant>,
     pub read_only: bool,
     pub reminders: Option<Value>,
-    pub status: String,
-    pub title: String,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
     pub visibility: Option<Value>,
     pub when: HashMap<String, Value>,
 }
@@ -71,9 +98,14 @@ pub struct Participant {
 }
 
 /// Represents a folder.
+///
+/// The `name` holds the system role of the folder (e.g. `inbox`, `sent`, `trash`) and is
+/// `None` for user-created folders.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Folder {
-    // TODO
+    pub display_name: String,
+    pub id: String,
+    pub name: Option<String>,
 }
 
 /// Represents a label.
@@ -138,43 +170,27 @@ impl Message {
     /// - `"body"`: Check if the message's body contains a specific keyword.
     /// - `"thread_id"`: Check if the message belongs to a specific thread (by ID).
     /// - `"labels"`: Check if the message is labeled with specific labels (comma-separated).
-    fn matches_filter(&self, filter: &HashMap<&str, &str>) -> bool {
+    pub(crate) fn matches_filter(&self, filter: &HashMap<&str, &str>) -> bool {
         if let Some(to) = filter.get("to") {
-            if !self
-                .to
-                .iter()
-                .any(|recipient| recipient.email == to.to_string())
-            {
+            if !self.to.iter().any(|recipient| recipient.email == *to) {
                 return false;
             }
         }
 
         if let Some(from) = filter.get("from") {
-            if !self
-                .from
-                .iter()
-                .any(|sender| sender.email == from.to_string())
-            {
+            if !self.from.iter().any(|sender| sender.email == *from) {
                 return false;
             }
         }
 
         if let Some(cc) = filter.get("cc") {
-            if !self
-                .cc
-                .iter()
-                .any(|recipient| recipient.email == cc.to_string())
-            {
+            if !self.cc.iter().any(|recipient| recipient.email == *cc) {
                 return false;
             }
         }
 
         if let Some(bcc) = filter.get("bcc") {
-            if !self
-                .bcc
-                .iter()
-                .any(|recipient| recipient.email == bcc.to_string())
-            {
+            if !self.bcc.iter().any(|recipient| recipient.email == *bcc) {
                 return false;
             }
         }
@@ -219,7 +235,7 @@ impl Message {
         }
 
         if let Some(thread_id) = filter.get("thread_id") {
-            if self.thread_id != thread_id.to_string() {
+            if self.thread_id != *thread_id {
                 return false;
             }
         }
@@ -228,7 +244,7 @@ impl Message {
             let filter_labels: Vec<&str> = labels.split(',').collect();
             if !filter_labels
                 .iter()
-                .any(|label| self.labels.iter().any(|l| l.name == label.to_string()))
+                .any(|label| self.labels.iter().any(|l| l.name == *label))
             {
                 return false;
             }
@@ -239,20 +255,62 @@ impl Message {
     }
 }
 
+/// Check the criteria of a message filter which must be parsed: `date` must be a Unix timestamp,
+/// and `unread` and `starred` must be `true` or `false`.
+pub(crate) fn check_filter(filter: &HashMap<&str, &str>) -> Result<(), String> {
+    filter_date(filter)?;
+    filter_flag(filter, "unread")?;
+    filter_flag(filter, "starred")?;
+    Ok(())
+}
+
+/// The `date` criterion of a message filter.
+pub(crate) fn filter_date(filter: &HashMap<&str, &str>) -> Result<Option<i64>, String> {
+    filter
+        .get("date")
+        .map(|date| {
+            date.parse::<i64>().map_err(|_| {
+                format!(
+                    "Invalid filter `date`: expected a Unix timestamp, got `{}`.",
+                    date
+                )
+            })
+        })
+        .transpose()
+}
+
+/// A `true` or `false` criterion of a message filter, e.g. `unread`.
+pub(crate) fn filter_flag(
+    filter: &HashMap<&str, &str>,
+    field: &str,
+) -> Result<Option<bool>, String> {
+    filter
+        .get(field)
+        .map(|flag| {
+            flag.parse::<bool>().map_err(|_| {
+                format!(
+                    "Invalid filter `{}`: expected true or false, got `{}`.",
+                    field, flag
+                )
+            })
+        })
+        .transpose()
+}
+
 /// Struct for working with Nylas messages.
 pub struct Messages<'a> {
-    pub nylas: &'a mut Nylas,
+    pub nylas: &'a Nylas,
 }
 
 impl<'a> Messages<'a> {
-    pub fn new(nylas: &'a mut Nylas) -> Self {
+    pub fn new(nylas: &'a Nylas) -> Self {
         Messages { nylas }
     }
     /// Retrieve all messages from the Nylas API.
     ///
     /// # Arguments
     ///
-    /// - `self`: A mutable reference to the `Messages` struct.
+    /// - `self`: A reference to the `Messages` struct.
     ///
     /// # Returns
     ///
@@ -264,7 +322,7 @@ impl<'a> Messages<'a> {
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use nylas::client::Nylas;
     /// #[tokio::main]
     /// async fn main() {
@@ -272,7 +330,7 @@ impl<'a> Messages<'a> {
     ///     let client_secret = "YOUR_CLIENT_SECRET";
     ///     let access_token = "YOUR_ACCESS_TOKEN";
     ///
-    ///     // let mut nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///     // let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
     ///
     ///     // Call the all method to retrieve all messages
     ///     // let messages = nylas.messages().all().await;
@@ -280,7 +338,7 @@ impl<'a> Messages<'a> {
     ///     //     Ok(messages) => {
     ///     //         for message in messages {
     ///     //             // Process each message
-    ///     //             println!("{:?}", nylas.messages);
+    ///     //             println!("{:?}", message);
     ///     //         }
     ///     //     }
     ///     //     Err(err) => {
@@ -290,33 +348,21 @@ impl<'a> Messages<'a> {
     ///     // }
     /// # }
     /// ```
-    pub async fn all(&mut self) -> Result<Vec<Message>, String> {
+    pub async fn all(&self) -> Result<Vec<Message>, String> {
         // Construct the API URL
         let url = "https://api.nylas.com/messages";
 
-        // Create an HTTP client with the bearer token in the headers
-        let client = reqwest::Client::new();
-        let request = client
-            .get(url)
-            .header("Accept", "application/json")
-            .header(
-                "Authorization",
-                format!(
-                    "Bearer {}",
-                    self.nylas
-                        .access_token
-                        .as_ref()
-                        .ok_or("Access token not provided")?
-                ),
-            )
-            .send();
+        let request = self.nylas.send(self.nylas.request(Method::GET, url).await?);
 
         // Handle the HTTP response
         match request.await {
             Ok(response) => {
                 if response.status().is_success() {
                     // Parse the JSON response into a vector of Message
-                    let messages: Vec<Message> = response.json().await.unwrap();
+                    let messages: Vec<Message> = response
+                        .json()
+                        .await
+                        .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
                     // Set the messages attribute
                     // self.nylas.messages = Some(messages.clone());
                     Ok(messages)
@@ -333,7 +379,7 @@ impl<'a> Messages<'a> {
     ///
     /// # Arguments
     ///
-    /// - `self`: A mutable reference to the `Messages` struct.
+    /// - `self`: A reference to the `Messages` struct.
     /// - `query`: A search query string.
     /// - `limit`: An optional limit to specify the number of results to retrieve.
     /// - `offset`: An optional offset to specify the starting point of results.
@@ -344,7 +390,7 @@ impl<'a> Messages<'a> {
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// # use nylas::client::Nylas;
     /// #[tokio::main]
     /// async fn main() {
@@ -352,9 +398,9 @@ impl<'a> Messages<'a> {
     ///     let client_secret = "YOUR_CLIENT_SECRET";
     ///     let access_token = "YOUR_ACCESS_TOKEN";
     ///
-    ///     let mut nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
     ///
-    ///     Call the `search` method to search for messages with a limit and offset
+    ///     // Call the `search` method to search for messages with a limit and offset
     ///     let result = nylas.messages().search("yo.code.inbox@gmail.com", Some(1), Some(0)).await;
     ///     match result {
     ///         Ok(messages) => {
@@ -371,7 +417,7 @@ impl<'a> Messages<'a> {
     /// }
     /// ```
     pub async fn search(
-        &mut self,
+        &self,
         query: &str,
         limit: Option<i32>,
         offset: Option<i32>,
@@ -387,29 +433,19 @@ impl<'a> Messages<'a> {
             url.push_str(&format!("&offset={}", offset));
         }
 
-        // Create an HTTP client with the bearer token in the headers
-        let client = reqwest::Client::new();
-        let request = client
-            .get(&url)
-            .header("Accept", "application/json")
-            .header(
-                "Authorization",
-                format!(
-                    "Bearer {}",
-                    self.nylas
-                        .access_token
-                        .as_ref()
-                        .ok_or("Access token not provided")?,
-                ),
-            )
-            .send();
+        let request = self
+            .nylas
+            .send(self.nylas.request(Method::GET, &url).await?);
 
         // Handle the HTTP response
         match request.await {
             Ok(response) => {
                 if response.status().is_success() {
                     // Parse the JSON response into a vector of Message
-                    let messages: Vec<Message> = response.json().await.unwrap();
+                    let messages: Vec<Message> = response
+                        .json()
+                        .await
+                        .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
                     Ok(messages)
                 } else {
                     Err(format!("Request failed with status: {}", response.status()))
@@ -423,7 +459,7 @@ impl<'a> Messages<'a> {
     ///
     /// # Arguments
     ///
-    /// - `self`: A mutable reference to the `Messages` struct.
+    /// - `self`: A reference to the `Messages` struct.
     /// - `filter`: An optional hashmap containing filtering criteria as key-value pairs.
     /// - `view`: An optional view parameter (enum) for the message. Allowed values: ids, count, expanded.
     ///
@@ -435,24 +471,27 @@ impl<'a> Messages<'a> {
     ///
     /// This method can return an error if the access token is not provided, if the request to the Nylas API fails when calling the `all` method, or if there's an issue filtering the messages.
     ///
+    /// The filter is rejected before any request if `date` isn't a Unix timestamp or if `unread` or `starred` isn't `true` or `false`.
+    ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use nylas::client::Nylas;
     /// use nylas::messages::View;
+    /// use std::collections::HashMap;
     /// #[tokio::main]
     /// async fn main() {
     ///     let client_id = "YOUR_CLIENT_ID";
     ///     let client_secret = "YOUR_CLIENT_SECRET";
     ///     let access_token = "YOUR_ACCESS_TOKEN";
     ///
-    ///     let mut nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
     ///
-    ///     Define filter parameters as a HashMap
+    ///     // Define filter parameters as a HashMap
     ///     let mut filter = HashMap::new();
     ///     filter.insert("to", "oss@wiseai.dev");
-    ///     
-    ///     Call the `where_` method with filter and view parameters
+    ///
+    ///     // Call the `where_` method with filter and view parameters
     ///     let result = nylas.messages().where_(Some(filter), Some(View::Expanded)).await;
     ///     match result {
     ///         Ok(messages) => {
@@ -485,7 +524,7 @@ impl<'a> Messages<'a> {
     /// - `"thread_id"`: Filter messages belonging to a specific thread (by ID).
     /// - `"labels"`: Filter messages with specific labels (comma-separated).
     pub async fn where_(
-        &mut self,
+        &self,
         filter: Option<HashMap<&str, &str>>,
         view: Option<View>,
     ) -> Result<Vec<Message>, String> {
@@ -493,32 +532,22 @@ impl<'a> Messages<'a> {
         let mut url = "https://api.nylas.com/messages".to_string();
 
         if let Some(view) = view {
-            url.push_str(&format!("?view={}", view.to_string()));
+            url.push_str(&format!("?view={}", view));
         }
 
-        // Create an HTTP client with the bearer token in the headers
-        let client = reqwest::Client::new();
-        let request = client
-            .get(&url)
-            .header("Accept", "application/json")
-            .header(
-                "Authorization",
-                format!(
-                    "Bearer {}",
-                    self.nylas
-                        .access_token
-                        .as_ref()
-                        .ok_or("Access token not provided")?,
-                ),
-            )
-            .send();
+        let request = self
+            .nylas
+            .send(self.nylas.request(Method::GET, &url).await?);
 
         // Handle the HTTP response
         match request.await {
             Ok(response) => {
                 if response.status().is_success() {
                     // Parse the JSON response into a vector of Message
-                    let messages: Vec<Message> = response.json().await.unwrap();
+                    let messages: Vec<Message> = response
+                        .json()
+                        .await
+                        .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
                     // Filter messages based on the provided parameters
                     let filtered_messages: Vec<Message> = match filter {
                         Some(filter) => messages
@@ -536,11 +565,110 @@ impl<'a> Messages<'a> {
         }
     }
 
+    /// Filters messages with a Gmail-style query, e.g.
+    /// `from:alice is:unread has:attachment after:2024/01/01 subject:"invoice"`.
+    ///
+    /// The criteria supported by the Nylas API are sent as request parameters, the others are
+    /// evaluated on the returned messages. See [`Query`] for the supported operators.
+    ///
+    /// # Arguments
+    ///
+    /// - `self`: A reference to the `Messages` struct.
+    /// - `query`: The query string.
+    /// - `limit`: An optional limit to specify the number of messages to request.
+    /// - `offset`: An optional offset to specify the starting point of the requested messages.
+    ///
+    /// The limit and offset apply to the messages returned by the Nylas API, before the local
+    /// filtering, so a page may contain fewer than `limit` matching messages.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of messages matching the query if successful, or an error
+    /// message.
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the query can't be parsed, if the access token is not
+    /// provided, or if the request to the Nylas API fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use nylas::client::Nylas;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client_id = "YOUR_CLIENT_ID";
+    ///     let client_secret = "YOUR_CLIENT_SECRET";
+    ///     let access_token = "YOUR_ACCESS_TOKEN";
+    ///
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///
+    ///     let result = nylas
+    ///         .messages()
+    ///         .query(r#"from:alice is:unread has:attachment subject:"invoice""#, Some(50), None)
+    ///         .await;
+    ///     match result {
+    ///         Ok(messages) => {
+    ///             for message in messages {
+    ///                 println!("{}", message.subject);
+    ///             }
+    ///         }
+    ///         Err(err) => {
+    ///             eprintln!("Error: {}", err);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn query(
+        &self,
+        query: &str,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<Message>, String> {
+        let query: Query = query.parse()?;
+
+        // Construct the API URL with the criteria supported by Nylas
+        let mut url = Url::parse("https://api.nylas.com/messages").map_err(|e| e.to_string())?;
+        {
+            let mut pairs = url.query_pairs_mut();
+            for (name, value) in query.params() {
+                pairs.append_pair(name, &value);
+            }
+            if let Some(limit) = limit {
+                pairs.append_pair("limit", &limit.to_string());
+            }
+            if let Some(offset) = offset {
+                pairs.append_pair("offset", &offset.to_string());
+            }
+        }
+
+        let response = self
+            .nylas
+            .send(self.nylas.request(Method::GET, url).await?)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            return Err(format!("Request failed with status: {}", response.status()));
+        }
+
+        let messages: Vec<Message> = response
+            .json()
+            .await
+            .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
+
+        // Evaluate the whole query, the API only knows part of it
+        Ok(messages
+            .into_iter()
+            .filter(|message| query.matches(message))
+            .collect())
+    }
+
     /// Retrieve the most recent message from the Nylas API.
     ///
     /// # Arguments
     ///
-    /// - `self`: A mutable reference to the `Messages` struct.
+    /// - `self`: A reference to the `Messages` struct.
     ///
     /// # Returns
     ///
@@ -548,7 +676,7 @@ impl<'a> Messages<'a> {
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use nylas::client::Nylas;
     /// #[tokio::main]
     /// async fn main() {
@@ -556,7 +684,7 @@ impl<'a> Messages<'a> {
     ///     let client_secret = "YOUR_CLIENT_SECRET";
     ///     let access_token = "YOUR_ACCESS_TOKEN";
     ///
-    ///     let mut nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
     ///
     ///     let message_result = nylas.messages().first().await;
     ///     match message_result {
@@ -578,13 +706,13 @@ impl<'a> Messages<'a> {
     /// # Errors
     ///
     /// This method can return an error if the access token is not provided, or if the request to the Nylas API fails.
-    pub async fn first(&mut self) -> Result<Option<Message>, String> {
+    pub async fn first(&self) -> Result<Option<Message>, String> {
         // Retrieve all messages
         let all_messages = self.all().await?;
 
         // Sort the messages by the "date" field in descending order (most recent first)
         let mut sorted_messages = all_messages.clone();
-        sorted_messages.sort_by(|a, b| b.date.cmp(&a.date));
+        sorted_messages.sort_by_key(|message| std::cmp::Reverse(message.date));
 
         // If there are messages, return the first one; otherwise, return None
         if !sorted_messages.is_empty() {
@@ -598,7 +726,7 @@ impl<'a> Messages<'a> {
     ///
     /// # Arguments
     ///
-    /// - `self`: A mutable reference to the `Messages` struct.
+    /// - `self`: A reference to the `Messages` struct.
     /// - `id`: The ID of the message you want to retrieve.
     /// - `view`: An optional view parameter (enum) for the message. Allowed values: ids, count, expanded.
     ///
@@ -608,7 +736,7 @@ impl<'a> Messages<'a> {
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use nylas::client::Nylas;
     /// use nylas::messages::View;
     /// #[tokio::main]
@@ -617,9 +745,9 @@ impl<'a> Messages<'a> {
     ///     let client_secret = "YOUR_CLIENT_SECRET";
     ///     let access_token = "YOUR_ACCESS_TOKEN";
     ///
-    ///     let mut nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
+    ///     let nylas = Nylas::new(client_id, client_secret, Some(access_token)).await.unwrap();
     ///
-    ///     Retrieve a specific message by ID with a view parameter
+    ///     // Retrieve a specific message by ID with a view parameter
     ///     let message_id = "your_message_id_here";
     ///     let message_result = nylas.messages().get(message_id, Some(View::Expanded)).await;
     ///     match message_result {
@@ -638,30 +766,17 @@ impl<'a> Messages<'a> {
     ///     }
     /// }
     /// ```
-    pub async fn get(&mut self, id: &str, view: Option<View>) -> Result<Option<Message>, String> {
+    pub async fn get(&self, id: &str, view: Option<View>) -> Result<Option<Message>, String> {
         // Construct the API URL for the specific message with view parameter
         let mut url = format!("https://api.nylas.com/messages/{}", id);
 
         if let Some(view) = view {
-            url.push_str(&format!("?view={}", view.to_string()));
+            url.push_str(&format!("?view={}", view));
         }
 
-        // Create an HTTP client with the bearer token in the headers
-        let client = reqwest::Client::new();
-        let request = client
-            .get(&url)
-            .header("Accept", "application/json")
-            .header(
-                "Authorization",
-                format!(
-                    "Bearer {}",
-                    self.nylas
-                        .access_token
-                        .as_ref()
-                        .ok_or("Access token not provided")?,
-                ),
-            )
-            .send();
+        let request = self
+            .nylas
+            .send(self.nylas.request(Method::GET, &url).await?);
 
         // Handle the HTTP response
         match request.await {
@@ -680,3 +795,35 @@ impl<'a> Messages<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn events_with_null_fields_are_read() {
+        let event: Event = serde_json::from_value(json!({
+            "account_id": "a1", "busy": true, "calendar_id": "c1", "description": null,
+            "id": "e1", "location": null, "message_id": null, "object": "event", "owner": null,
+            "participants": [], "read_only": false, "reminders": null, "status": null,
+            "title": null, "visibility": null, "when": {}
+        }))
+        .unwrap();
+        assert_eq!(event.description, None);
+        assert_eq!(event.location, None);
+        assert_eq!(event.message_id, None);
+        assert_eq!(event.owner, None);
+        assert_eq!(event.status, None);
+        assert_eq!(event.title, None);
+
+        // Missing fields are read as `None` as well
+        let event: Event = serde_json::from_value(json!({
+            "account_id": "a1", "busy": true, "calendar_id": "c1", "id": "e1",
+            "object": "event", "participants": [], "read_only": false, "reminders": null,
+            "visibility": null, "when": {}
+        }))
+        .unwrap();
+        assert_eq!(event.title, None);
+    }
+}
@@ -0,0 +1,255 @@
+//! Metrics of the requests sent to Nylas.
+//!
+//! Every request is recorded through the [metrics](https://docs.rs/metrics) facade, so the
+//! metrics end up in whichever exporter the application installed (Prometheus, StatsD, ...).
+//! Requests are labeled with their `method` and `endpoint`, the path of the request with the
+//! IDs replaced by `{id}`, e.g. `/messages/{id}`.
+//!
+//! - [`REQUESTS`]: Counter of requests, also labeled with the response `status`.
+//! - [`REQUEST_DURATION`]: Histogram of the request latencies in seconds, retries included.
+//! - [`ERRORS`]: Counter of failed requests, labeled with the error `kind`: `client_error`,
+//!   `server_error`, `timeout`, `connect`, `request`, `body`, `decode` or `other`.
+//! - [`RATE_LIMITED`]: Counter of `429 Too Many Requests` responses, each counted once: the
+//!   retried ones when they are retried, the last one with the outcome of the request.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nylas::client::Nylas;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     // Install an exporter of your choice first, then describe the metrics once
+//!     nylas::metrics::describe();
+//!
+//!     let access_token = "YOUR_ACCESS_TOKEN";
+//!     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", Some(access_token))
+//!         .await
+//!         .unwrap();
+//!     let messages = nylas.messages().all().await;
+//! }
+//! ```
+
+use crate::telemetry::path_template;
+use ::metrics::{counter, describe_counter, describe_histogram, histogram, Unit};
+use reqwest::{Method, Response, StatusCode, Url};
+use std::time::Duration;
+
+pub const REQUESTS: &str = "nylas_requests_total";
+pub const REQUEST_DURATION: &str = "nylas_request_duration_seconds";
+pub const ERRORS: &str = "nylas_request_errors_total";
+pub const RATE_LIMITED: &str = "nylas_rate_limited_total";
+
+/// Register the descriptions and units of the metrics with the installed recorder.
+pub fn describe() {
+    describe_counter!(REQUESTS, Unit::Count, "Requests sent to the Nylas API.");
+    describe_histogram!(
+        REQUEST_DURATION,
+        Unit::Seconds,
+        "Latency of the requests sent to the Nylas API, retries included."
+    );
+    describe_counter!(ERRORS, Unit::Count, "Failed requests to the Nylas API.");
+    describe_counter!(
+        RATE_LIMITED,
+        Unit::Count,
+        "Rate-limited responses of the Nylas API."
+    );
+}
+
+/// Record the outcome of a request.
+pub(crate) fn record(
+    method: &Method,
+    url: &Url,
+    result: &Result<Response, reqwest::Error>,
+    latency: Duration,
+) {
+    let method = method.to_string();
+    let endpoint = path_template(url);
+
+    histogram!(REQUEST_DURATION, "method" => method.clone(), "endpoint" => endpoint.clone())
+        .record(latency.as_secs_f64());
+
+    let kind = match result {
+        Ok(response) => {
+            let status = response.status();
+            counter!(
+                REQUESTS,
+                "method" => method.clone(),
+                "endpoint" => endpoint.clone(),
+                "status" => status.as_u16().to_string()
+            )
+            .increment(1);
+
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                counter!(RATE_LIMITED, "method" => method.clone(), "endpoint" => endpoint.clone())
+                    .increment(1);
+            }
+            if status.is_client_error() {
+                "client_error"
+            } else if status.is_server_error() {
+                "server_error"
+            } else {
+                return;
+            }
+        }
+        Err(error) => {
+            counter!(
+                REQUESTS,
+                "method" => method.clone(),
+                "endpoint" => endpoint.clone(),
+                "status" => "none"
+            )
+            .increment(1);
+            error_kind(error)
+        }
+    };
+
+    counter!(ERRORS, "method" => method, "endpoint" => endpoint, "kind" => kind).increment(1);
+}
+
+/// Record a rate-limited response which is about to be retried.
+pub(crate) fn record_rate_limited(method: &Method, url: &Url) {
+    counter!(
+        RATE_LIMITED,
+        "method" => method.to_string(),
+        "endpoint" => path_template(url)
+    )
+    .increment(1);
+}
+
+fn error_kind(error: &reqwest::Error) -> &'static str {
+    if error.is_timeout() {
+        "timeout"
+    } else if error.is_connect() {
+        "connect"
+    } else if error.is_request() {
+        "request"
+    } else if error.is_body() {
+        "body"
+    } else if error.is_decode() {
+        "decode"
+    } else {
+        "other"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Nylas;
+    use crate::retry::RetryPolicy;
+    use crate::test_support::{app, respond, respond_with, serve};
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use std::future::Future;
+    use std::net::{SocketAddr, TcpListener};
+
+    const MESSAGE_URL: &str = "https://api.nylas.com/messages/ax3ic1mdcdbbo2qd4jjm0ezwk";
+
+    // Run a future with a local recorder, returning the metrics as `name{labels}` along with
+    // their count of increments or samples
+    fn recorded<F: Future>(future: F) -> Vec<(String, u64)> {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        ::metrics::with_local_recorder(&recorder, || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(future)
+        });
+
+        let mut metrics: Vec<(String, u64)> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let labels: Vec<String> = key
+                    .key()
+                    .labels()
+                    .map(|label| format!("{}={}", label.key(), label.value()))
+                    .collect();
+                let count = match value {
+                    DebugValue::Counter(count) => count,
+                    DebugValue::Histogram(samples) => samples.len() as u64,
+                    DebugValue::Gauge(_) => unreachable!("no gauge is recorded"),
+                };
+                (
+                    format!("{}{{{}}}", key.key().name(), labels.join(",")),
+                    count,
+                )
+            })
+            .collect();
+        metrics.sort();
+        metrics
+    }
+
+    async fn get(nylas: Nylas) {
+        let request = nylas.app_request(Method::GET, MESSAGE_URL);
+        let _ = nylas.send(request).await;
+    }
+
+    fn metric(name: &str, labels: &str, count: u64) -> (String, u64) {
+        (format!("{}{{{}}}", name, labels), count)
+    }
+
+    #[test]
+    fn responses_are_labeled_with_the_endpoint_and_status() {
+        let (addr, _) = serve(vec![respond("404 Not Found", "{}")]);
+        let metrics = recorded(async { get(app(addr).await).await });
+        assert_eq!(
+            metrics,
+            vec![
+                metric(REQUEST_DURATION, "method=GET,endpoint=/messages/{id}", 1),
+                metric(
+                    ERRORS,
+                    "method=GET,endpoint=/messages/{id},kind=client_error",
+                    1
+                ),
+                metric(REQUESTS, "method=GET,endpoint=/messages/{id},status=404", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_have_a_kind_and_no_status() {
+        // Nothing listens on the port once the listener is dropped
+        let addr: SocketAddr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let metrics = recorded(async { get(app(addr).await).await });
+        assert!(metrics.contains(&metric(
+            ERRORS,
+            "method=GET,endpoint=/messages/{id},kind=connect",
+            1
+        )));
+        assert!(metrics.contains(&metric(
+            REQUESTS,
+            "method=GET,endpoint=/messages/{id},status=none",
+            1
+        )));
+    }
+
+    #[test]
+    fn rate_limited_responses_are_counted_once() {
+        let rate_limited = || respond_with("429 Too Many Requests", &[("retry-after", "0")], "");
+        let (addr, received) = serve(vec![rate_limited(), rate_limited()]);
+        let metrics = recorded(async {
+            let policy = RetryPolicy::default().with_max_attempts(2);
+            get(app(addr).await.with_retry_policy(policy)).await
+        });
+        assert_eq!(received.lock().unwrap().len(), 2);
+
+        // The retried response and the returned one
+        assert!(metrics.contains(&metric(
+            RATE_LIMITED,
+            "method=GET,endpoint=/messages/{id}",
+            2
+        )));
+        assert!(metrics.contains(&metric(
+            REQUESTS,
+            "method=GET,endpoint=/messages/{id},status=429",
+            1
+        )));
+    }
+}
@@ -0,0 +1,276 @@
+//! Hooks run around every request sent to Nylas.
+//!
+//! Implement [`Middleware`] to add headers, log or audit requests, or capture responses for
+//! debugging, and register it with `Nylas::with_middleware`. The `before_request` hooks run in
+//! registration order once per request, before the retries of the
+//! [`RetryPolicy`](crate::retry::RetryPolicy) and before the request is sent again with a fresh
+//! token from a [`TokenProvider`](crate::token::TokenProvider), and the `after_response` and `on_error` hooks run
+//! in reverse order with the final outcome.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nylas::client::Nylas;
+//! use nylas::middleware::{Middleware, RequestId};
+//! use reqwest::{Request, Response};
+//!
+//! struct AuditLog;
+//!
+//! impl Middleware for AuditLog {
+//!     fn after_response(&self, request: &Request, response: &Response) {
+//!         println!("{} {} -> {}", request.method(), request.url().path(), response.status());
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let access_token = "YOUR_ACCESS_TOKEN";
+//!     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", Some(access_token))
+//!         .await
+//!         .unwrap()
+//!         .with_middleware(RequestId::default())
+//!         .with_middleware(AuditLog);
+//!
+//!     let messages = nylas.messages().all().await;
+//! }
+//! ```
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Request, Response};
+
+/// A hook run around every request sent to Nylas.
+pub trait Middleware: Send + Sync {
+    /// Called before the request is sent, e.g. to add headers.
+    fn before_request(&self, _request: &mut Request) {}
+
+    /// Called with the final response, whatever its status.
+    ///
+    /// The request holds the method, URL and headers that were sent, but not the body.
+    fn after_response(&self, _request: &Request, _response: &Response) {}
+
+    /// Called when the request couldn't be sent or no response was received.
+    fn on_error(&self, _request: &Request, _error: &reqwest::Error) {}
+}
+
+/// Adds a random request id header to every request which doesn't have one yet.
+#[derive(Debug, Clone)]
+pub struct RequestId {
+    header: HeaderName,
+}
+
+impl RequestId {
+    /// Use a custom header instead of `X-Request-Id`.
+    pub fn new(header: HeaderName) -> Self {
+        RequestId { header }
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        RequestId::new(HeaderName::from_static("x-request-id"))
+    }
+}
+
+impl Middleware for RequestId {
+    fn before_request(&self, request: &mut Request) {
+        if request.headers().contains_key(&self.header) {
+            return;
+        }
+        let id = format!("{:016x}{:016x}", fastrand::u64(..), fastrand::u64(..));
+        if let Ok(value) = HeaderValue::from_str(&id) {
+            request.headers_mut().insert(self.header.clone(), value);
+        }
+    }
+}
+
+/// Adds fixed headers to every request, replacing the headers of the same name.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::middleware::{Headers, Middleware};
+/// use reqwest::header::HeaderMap;
+/// use reqwest::{Method, Request};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("X-Tenant", "acme".parse().unwrap());
+/// let middleware = Headers::new(headers);
+///
+/// let mut request = Request::new(Method::GET, "https://api.nylas.com/messages".parse().unwrap());
+/// middleware.before_request(&mut request);
+/// assert_eq!(request.headers()["X-Tenant"], "acme");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Headers {
+    headers: HeaderMap,
+}
+
+impl Headers {
+    pub fn new(headers: HeaderMap) -> Self {
+        Headers { headers }
+    }
+}
+
+impl Middleware for Headers {
+    fn before_request(&self, request: &mut Request) {
+        for (name, value) in &self.headers {
+            request.headers_mut().insert(name.clone(), value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Nylas;
+    use crate::test_support::{app, header, respond, serve};
+    use crate::token::{TokenFuture, TokenProvider};
+    use reqwest::Method;
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    type Calls = Arc<Mutex<Vec<String>>>;
+
+    // Records the hooks it is called with
+    struct Record(&'static str, Calls);
+
+    impl Middleware for Record {
+        fn before_request(&self, _request: &mut Request) {
+            self.1.lock().unwrap().push(format!("{} before", self.0));
+        }
+
+        fn after_response(&self, _request: &Request, response: &Response) {
+            let call = format!("{} after {}", self.0, response.status().as_u16());
+            self.1.lock().unwrap().push(call);
+        }
+
+        fn on_error(&self, _request: &Request, _error: &reqwest::Error) {
+            self.1.lock().unwrap().push(format!("{} error", self.0));
+        }
+    }
+
+    // Hands out a new token every time
+    struct Rotating(AtomicUsize);
+
+    impl TokenProvider for Rotating {
+        fn token(&self) -> TokenFuture<'_> {
+            let token = format!("t{}", self.0.fetch_add(1, Ordering::SeqCst));
+            Box::pin(async move { Ok(token) })
+        }
+
+        fn invalidate(&self, _token: &str) -> bool {
+            true
+        }
+    }
+
+    fn recorded(nylas: Nylas, calls: &Calls) -> Nylas {
+        nylas
+            .with_middleware(Record("first", calls.clone()))
+            .with_middleware(Record("second", calls.clone()))
+    }
+
+    async fn get(nylas: &Nylas) -> Result<Response, reqwest::Error> {
+        let request = nylas
+            .request(Method::GET, "https://api.nylas.com/messages")
+            .await
+            .unwrap();
+        nylas.send(request).await
+    }
+
+    #[tokio::test]
+    async fn hooks_run_in_order_and_responses_in_reverse() {
+        let (addr, _) = serve(vec![respond("200 OK", "[]")]);
+        let calls = Calls::default();
+        let nylas = recorded(app(addr).await.with_access_token("TOKEN"), &calls);
+
+        get(&nylas).await.unwrap();
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                "first before",
+                "second before",
+                "second after 200",
+                "first after 200"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_reach_every_middleware_in_reverse() {
+        // Nothing listens on the port once the listener is dropped
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let calls = Calls::default();
+        let nylas = recorded(app(addr).await.with_access_token("TOKEN"), &calls);
+
+        assert!(get(&nylas).await.is_err());
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                "first before",
+                "second before",
+                "second error",
+                "first error"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn hooks_run_once_when_a_rejected_token_is_replaced() {
+        let (addr, received) = serve(vec![
+            respond("401 Unauthorized", "{}"),
+            respond("200 OK", "[]"),
+        ]);
+        let calls = Calls::default();
+        let nylas = recorded(app(addr).await, &calls)
+            .with_middleware(RequestId::default())
+            .with_token_provider(Rotating(AtomicUsize::new(0)));
+
+        get(&nylas).await.unwrap();
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                "first before",
+                "second before",
+                "second after 200",
+                "first after 200"
+            ]
+        );
+        let received = received.lock().unwrap();
+        assert_eq!(header(&received[0], "authorization"), Some("Bearer t0"));
+        assert_eq!(header(&received[1], "authorization"), Some("Bearer t1"));
+        assert_eq!(
+            header(&received[0], "x-request-id"),
+            header(&received[1], "x-request-id")
+        );
+    }
+
+    #[tokio::test]
+    async fn request_id_keeps_an_existing_header() {
+        let (addr, received) = serve(vec![respond("200 OK", "[]"), respond("200 OK", "[]")]);
+        let nylas = app(addr)
+            .await
+            .with_access_token("TOKEN")
+            .with_middleware(RequestId::default());
+
+        let request = nylas
+            .request(Method::GET, "https://api.nylas.com/messages")
+            .await
+            .unwrap()
+            .header("X-Request-Id", "mine");
+        nylas.send(request).await.unwrap();
+        get(&nylas).await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(header(&received[0], "x-request-id"), Some("mine"));
+        let generated = header(&received[1], "x-request-id").unwrap();
+        assert_eq!(generated.len(), 32);
+        assert!(generated.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+}
@@ -0,0 +1,490 @@
+//! Native authentication.
+//!
+//! Native authentication connects an account without any browser: the application sends the
+//! credentials of the account to `/connect/authorize`, which answers with a code, then exchanges
+//! the code for an access token with `/connect/token`. It suits headless onboarding of IMAP and
+//! Exchange accounts, or of Google and Microsoft accounts whose refresh token the application
+//! already has.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nylas::client::Nylas;
+//! use nylas::native_auth::{NativeAuthentication, ProviderSettings};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let app = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+//!
+//!     let settings = ProviderSettings::Exchange {
+//!         username: "jane@example.com".to_string(),
+//!         password: "PASSWORD".to_string(),
+//!         eas_server_host: Some("mail.example.com".to_string()),
+//!     };
+//!     let request = NativeAuthentication::new("Jane Doe", "jane@example.com", settings)
+//!         .with_scopes(&["email.read_only", "calendar"]);
+//!
+//!     let token = app.native_auth().authenticate(&request).await.unwrap();
+//!     let nylas = app.with_token(&token);
+//! }
+//! ```
+
+use crate::auth::{self, TokenResponse};
+use crate::client::Nylas;
+use reqwest::{Method, Response};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The URL sending the credentials of an account.
+pub const AUTHORIZE_URL: &str = "https://api.nylas.com/connect/authorize";
+/// The URL exchanging the code of `/connect/authorize` for an access token.
+pub const TOKEN_URL: &str = "https://api.nylas.com/connect/token";
+
+/// The credentials of an account, by provider.
+///
+/// The `Debug` output only shows the provider, never the credentials.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::native_auth::ProviderSettings;
+///
+/// let settings = ProviderSettings::Exchange {
+///     username: "jane@example.com".to_string(),
+///     password: "PASSWORD".to_string(),
+///     eas_server_host: None,
+/// };
+/// assert_eq!(
+///     serde_json::to_string(&settings).unwrap(),
+///     r#"{"username":"jane@example.com","password":"PASSWORD"}"#
+/// );
+/// ```
+#[derive(Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ProviderSettings {
+    /// Any IMAP server, with the SMTP server used to send messages.
+    Imap {
+        imap_host: String,
+        imap_port: u16,
+        imap_username: String,
+        imap_password: String,
+        smtp_host: String,
+        smtp_port: u16,
+        smtp_username: String,
+        smtp_password: String,
+        /// Whether to require SSL/TLS connections to both servers.
+        ssl_required: bool,
+    },
+    /// Microsoft Exchange through Exchange ActiveSync.
+    Exchange {
+        username: String,
+        password: String,
+        /// The ActiveSync server, detected from the email address when `None`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        eas_server_host: Option<String>,
+    },
+    /// A Google account, with a refresh token of your own Google OAuth client.
+    Google {
+        google_client_id: String,
+        google_client_secret: String,
+        google_refresh_token: String,
+    },
+    /// A Google Workspace account, through a service account with domain-wide delegation.
+    GoogleServiceAccount {
+        /// The JSON key of the service account.
+        service_account_json: serde_json::Value,
+    },
+    /// A Microsoft 365 account, with a refresh token of your own Microsoft application.
+    Office365 {
+        microsoft_client_id: String,
+        microsoft_client_secret: String,
+        microsoft_refresh_token: String,
+        redirect_uri: String,
+    },
+}
+
+impl ProviderSettings {
+    /// The `provider` sent with the settings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nylas::native_auth::ProviderSettings;
+    ///
+    /// let settings = ProviderSettings::Google {
+    ///     google_client_id: "GOOGLE_CLIENT_ID".to_string(),
+    ///     google_client_secret: "GOOGLE_CLIENT_SECRET".to_string(),
+    ///     google_refresh_token: "GOOGLE_REFRESH_TOKEN".to_string(),
+    /// };
+    /// assert_eq!(settings.provider(), "gmail");
+    /// assert!(!format!("{:?}", settings).contains("GOOGLE_CLIENT_SECRET"));
+    /// ```
+    pub fn provider(&self) -> &'static str {
+        match self {
+            ProviderSettings::Imap { .. } => "imap",
+            ProviderSettings::Exchange { .. } => "exchange",
+            ProviderSettings::Google { .. } | ProviderSettings::GoogleServiceAccount { .. } => {
+                "gmail"
+            }
+            ProviderSettings::Office365 { .. } => "office365",
+        }
+    }
+}
+
+impl fmt::Debug for ProviderSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProviderSettings")
+            .field("provider", &self.provider())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The account to connect with native authentication.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeAuthentication {
+    /// The name of the user, shown as the sender of their messages.
+    pub name: String,
+    pub email_address: String,
+    pub settings: ProviderSettings,
+    /// The scopes requested, e.g. `email.read_only` or `calendar`.
+    pub scopes: Vec<String>,
+    /// The account to re-authenticate, e.g. after its password changed.
+    pub reauth_account_id: Option<String>,
+}
+
+impl NativeAuthentication {
+    pub fn new(name: &str, email_address: &str, settings: ProviderSettings) -> Self {
+        NativeAuthentication {
+            name: name.to_string(),
+            email_address: email_address.to_string(),
+            settings,
+            scopes: Vec::new(),
+            reauth_account_id: None,
+        }
+    }
+
+    pub fn with_scopes<S: AsRef<str>>(mut self, scopes: &[S]) -> Self {
+        self.scopes = scopes.iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
+    pub fn with_reauth_account_id(mut self, account_id: &str) -> Self {
+        self.reauth_account_id = Some(account_id.to_string());
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct AuthorizeBody<'a> {
+    client_id: &'a str,
+    name: &'a str,
+    email_address: &'a str,
+    provider: &'a str,
+    settings: &'a ProviderSettings,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scopes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reauth_account_id: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct AuthorizeResponse {
+    code: String,
+}
+
+/// Struct for connecting accounts with native authentication.
+///
+/// Requests are authenticated with the client ID and client secret stored on `Nylas` and don't
+/// require an access token.
+pub struct NativeAuth<'a> {
+    pub nylas: &'a Nylas,
+}
+
+impl<'a> NativeAuth<'a> {
+    pub fn new(nylas: &'a Nylas) -> Self {
+        NativeAuth { nylas }
+    }
+
+    /// Send the credentials of an account to Nylas.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the code to exchange with [`NativeAuth::token`], or an error
+    /// message.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the following conditions are not met:
+    /// 1. The client ID and client secret are not provided.
+    /// 2. The scopes are valid, see [`auth::join_scopes`].
+    /// 3. Nylas could connect to the account with the settings.
+    pub async fn authorize(&self, request: &NativeAuthentication) -> Result<String, String> {
+        self.check_credentials()?;
+
+        let scopes = if request.scopes.is_empty() {
+            None
+        } else {
+            Some(auth::join_scopes(&request.scopes)?)
+        };
+        let body = AuthorizeBody {
+            client_id: self.nylas.client_id(),
+            name: &request.name,
+            email_address: &request.email_address,
+            provider: request.settings.provider(),
+            settings: &request.settings,
+            scopes,
+            reauth_account_id: request.reauth_account_id.as_deref(),
+        };
+
+        let response = self
+            .nylas
+            .send(
+                self.nylas
+                    .app_request(Method::POST, AUTHORIZE_URL)
+                    .json(&body),
+            )
+            .await
+            .map_err(|e| format!("Request Error: {:?}", e))?;
+        let response: AuthorizeResponse = Self::parse(response).await?;
+        Ok(response.code)
+    }
+
+    /// Exchange the code of [`NativeAuth::authorize`] for an access token.
+    ///
+    /// The response of `/connect/token` is the connected account along with its access token,
+    /// read into the same model as the token exchange of hosted authentication.
+    pub async fn token(&self, code: &str) -> Result<TokenResponse, String> {
+        self.check_credentials()?;
+
+        let body = serde_json::json!({
+            "client_id": self.nylas.client_id(),
+            "client_secret": self.nylas.client_secret(),
+            "code": code,
+        });
+
+        let response = self
+            .nylas
+            .send(self.nylas.app_request(Method::POST, TOKEN_URL).json(&body))
+            .await
+            .map_err(|e| format!("Request Error: {:?}", e))?;
+        Self::parse(response).await
+    }
+
+    /// Connect an account: [`NativeAuth::authorize`] then [`NativeAuth::token`].
+    pub async fn authenticate(
+        &self,
+        request: &NativeAuthentication,
+    ) -> Result<TokenResponse, String> {
+        let code = self.authorize(request).await?;
+        self.token(&code).await
+    }
+
+    fn check_credentials(&self) -> Result<(), String> {
+        if self.nylas.client_id().is_empty() || self.nylas.client_secret().is_empty() {
+            return Err("Client ID and Client Secret must not be empty.".to_string());
+        }
+        Ok(())
+    }
+
+    async fn parse<T: serde::de::DeserializeOwned>(response: Response) -> Result<T, String> {
+        if response.status().is_success() {
+            response
+                .json()
+                .await
+                .map_err(|e| format!("JSON Parsing Error: {:?}", e))
+        } else {
+            // The error message tells which setting Nylas rejected
+            let error_message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown Error".to_string());
+            Err(format!("HTTP Error: - {}", error_message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app, ok, parse, respond, serve};
+    use serde_json::{json, Value};
+
+    fn imap() -> ProviderSettings {
+        ProviderSettings::Imap {
+            imap_host: "imap.example.com".to_string(),
+            imap_port: 993,
+            imap_username: "jane".to_string(),
+            imap_password: "IMAP_PASSWORD".to_string(),
+            smtp_host: "smtp.example.com".to_string(),
+            smtp_port: 465,
+            smtp_username: "jane".to_string(),
+            smtp_password: "SMTP_PASSWORD".to_string(),
+            ssl_required: true,
+        }
+    }
+
+    fn google() -> ProviderSettings {
+        ProviderSettings::Google {
+            google_client_id: "GOOGLE_CLIENT_ID".to_string(),
+            google_client_secret: "GOOGLE_CLIENT_SECRET".to_string(),
+            google_refresh_token: "GOOGLE_REFRESH_TOKEN".to_string(),
+        }
+    }
+
+    fn office365() -> ProviderSettings {
+        ProviderSettings::Office365 {
+            microsoft_client_id: "MICROSOFT_CLIENT_ID".to_string(),
+            microsoft_client_secret: "MICROSOFT_CLIENT_SECRET".to_string(),
+            microsoft_refresh_token: "MICROSOFT_REFRESH_TOKEN".to_string(),
+            redirect_uri: "https://example.com/callback".to_string(),
+        }
+    }
+
+    // The body sent to `/connect/authorize` for a request
+    async fn authorize(request: &NativeAuthentication) -> Value {
+        let (addr, received) = serve(vec![ok(&json!({ "code": "CODE" }))]);
+        let code = app(addr).await.native_auth().authorize(request).await;
+        assert_eq!(code, Ok("CODE".to_string()));
+
+        let received = received.lock().unwrap();
+        let (line, body) = parse(&received[0]);
+        assert_eq!(line, "POST /connect/authorize HTTP/1.1");
+        body
+    }
+
+    #[tokio::test]
+    async fn settings_are_sent_untagged_with_their_provider() {
+        let body = authorize(&NativeAuthentication::new(
+            "Jane",
+            "jane@example.com",
+            imap(),
+        ))
+        .await;
+        assert_eq!(
+            body,
+            json!({
+                "client_id": "CLIENT_ID",
+                "name": "Jane",
+                "email_address": "jane@example.com",
+                "provider": "imap",
+                "settings": {
+                    "imap_host": "imap.example.com", "imap_port": 993, "imap_username": "jane",
+                    "imap_password": "IMAP_PASSWORD", "smtp_host": "smtp.example.com",
+                    "smtp_port": 465, "smtp_username": "jane", "smtp_password": "SMTP_PASSWORD",
+                    "ssl_required": true,
+                },
+            })
+        );
+
+        let body = authorize(&NativeAuthentication::new(
+            "Jane",
+            "jane@example.com",
+            google(),
+        ))
+        .await;
+        assert_eq!(body["provider"], "gmail");
+        assert_eq!(
+            body["settings"],
+            json!({
+                "google_client_id": "GOOGLE_CLIENT_ID",
+                "google_client_secret": "GOOGLE_CLIENT_SECRET",
+                "google_refresh_token": "GOOGLE_REFRESH_TOKEN",
+            })
+        );
+
+        let body = authorize(&NativeAuthentication::new(
+            "Jane",
+            "jane@example.com",
+            office365(),
+        ))
+        .await;
+        assert_eq!(body["provider"], "office365");
+        assert_eq!(
+            body["settings"],
+            json!({
+                "microsoft_client_id": "MICROSOFT_CLIENT_ID",
+                "microsoft_client_secret": "MICROSOFT_CLIENT_SECRET",
+                "microsoft_refresh_token": "MICROSOFT_REFRESH_TOKEN",
+                "redirect_uri": "https://example.com/callback",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn scopes_are_joined_and_reauthentication_is_optional() {
+        let request = NativeAuthentication::new("Jane", "jane@example.com", google())
+            .with_scopes(&["email.read_only", "calendar", "email.read_only"])
+            .with_reauth_account_id("a1");
+        let body = authorize(&request).await;
+        assert_eq!(body["scopes"], "email.read_only,calendar");
+        assert_eq!(body["reauth_account_id"], "a1");
+
+        let body = authorize(&NativeAuthentication::new(
+            "Jane",
+            "jane@example.com",
+            google(),
+        ))
+        .await;
+        assert!(body.get("scopes").is_none());
+        assert!(body.get("reauth_account_id").is_none());
+    }
+
+    #[tokio::test]
+    async fn invalid_scopes_are_rejected_before_sending_the_credentials() {
+        let (addr, received) = serve(vec![]);
+        let request = NativeAuthentication::new("Jane", "jane@example.com", imap())
+            .with_scopes(&["email calendar"]);
+        assert!(app(addr)
+            .await
+            .native_auth()
+            .authorize(&request)
+            .await
+            .is_err());
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn codes_are_exchanged_for_a_token_response() {
+        let (addr, received) = serve(vec![
+            ok(&json!({ "code": "CODE" })),
+            ok(&json!({
+                "access_token": "TOKEN", "account_id": "a1", "email_address": "jane@example.com",
+                "provider": "gmail", "token_type": "bearer", "id": "a1", "object": "account",
+            })),
+        ]);
+        let request = NativeAuthentication::new("Jane", "jane@example.com", google());
+        let token = app(addr)
+            .await
+            .native_auth()
+            .authenticate(&request)
+            .await
+            .unwrap();
+        assert_eq!(token.access_token, "TOKEN");
+        assert_eq!(token.account_id.as_deref(), Some("a1"));
+        assert_eq!(token.provider.as_deref(), Some("gmail"));
+        assert_eq!(token.expires_at, None);
+
+        let received = received.lock().unwrap();
+        assert_eq!(
+            parse(&received[1]),
+            (
+                "POST /connect/token HTTP/1.1",
+                json!({ "client_id": "CLIENT_ID", "client_secret": "CLIENT_SECRET",
+                        "code": "CODE" })
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_settings_are_reported() {
+        let (addr, _) = serve(vec![respond(
+            "403 Forbidden",
+            r#"{"message": "Invalid IMAP password"}"#,
+        )]);
+        let request = NativeAuthentication::new("Jane", "jane@example.com", imap());
+        assert_eq!(
+            app(addr).await.native_auth().authorize(&request).await,
+            Err(r#"HTTP Error: - {"message": "Invalid IMAP password"}"#.to_string())
+        );
+    }
+}
@@ -0,0 +1,758 @@
+//! Gmail-style search queries.
+//!
+//! A [`Query`] is parsed from the syntax users already know from Gmail, e.g.
+//! `from:alice is:unread has:attachment after:2024/01/01 subject:"invoice"`, and turned into
+//! the filters supported by the Nylas API. Criteria the API can't express are evaluated locally
+//! with [`Query::matches`].
+//!
+//! # Supported Operators
+//!
+//! - `from:`, `to:`, `cc:`, `bcc:`: A full email address, matched exactly, or a part of an
+//!   address or name.
+//! - `subject:`: A word or a quoted phrase contained in the subject.
+//! - `in:`, `label:`: The name or ID of a folder or label, e.g. `in:inbox`.
+//! - `filename:`: A part of the name of an attachment.
+//! - `thread:`: The ID of a thread.
+//! - `is:unread`, `is:read`, `is:starred`, `is:unstarred`.
+//! - `has:attachment`.
+//! - `after:`, `before:`: A date as `YYYY/MM/DD` or `YYYY-MM-DD` (UTC), or a Unix timestamp.
+//! - `"`: Quotes a phrase, in which `\"` and `\\` stand for a quote and a backslash.
+//! - Any other word or quoted phrase is looked up in the subject, snippet and body, including
+//!   words with a colon which isn't one of the operators above, e.g. `re:invoice` or a URL.
+//!
+//! Prefix a criterion with `-` to negate it, e.g. `-from:noreply@example.com`. All criteria
+//! must match.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nylas::query::Query;
+//!
+//! let query: Query = r#"from:alice@example.com is:unread after:2024/01/01 subject:"invoice""#
+//!     .parse()
+//!     .unwrap();
+//!
+//! // Parameters sent to the Nylas API
+//! assert_eq!(
+//!     query.params(),
+//!     vec![
+//!         ("from", "alice@example.com".to_string()),
+//!         ("unread", "true".to_string()),
+//!         ("received_after", "1704067199".to_string()),
+//!     ]
+//! );
+//!
+//! // Unknown operators are plain text
+//! let query: Query = "re:invoice https://example.com/invoices".parse().unwrap();
+//! assert_eq!(query.to_string(), r#""re:invoice" "https://example.com/invoices""#);
+//! assert!(query.params().is_empty());
+//!
+//! // Parse errors point at the faulty criterion
+//! assert_eq!(
+//!     "is:unread before:2024/13/01".parse::<Query>().unwrap_err(),
+//!     "Invalid query at position 10: invalid date `2024/13/01`, expected YYYY/MM/DD"
+//! );
+//! ```
+
+use crate::messages::{EmailAddress, Message};
+use std::fmt;
+use std::str::FromStr;
+
+/// A criterion of a [`Query`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Criterion {
+    From(String),
+    To(String),
+    Cc(String),
+    Bcc(String),
+    Subject(String),
+    /// Folder or label name or ID.
+    In(String),
+    Filename(String),
+    Thread(String),
+    Unread(bool),
+    Starred(bool),
+    HasAttachment,
+    /// Received at or after the Unix timestamp.
+    After(i64),
+    /// Received before the Unix timestamp.
+    Before(i64),
+    /// Text contained in the subject, snippet or body.
+    Text(String),
+}
+
+/// A criterion, possibly negated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    pub negated: bool,
+    pub criterion: Criterion,
+}
+
+/// A parsed search query. All of its terms must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pub terms: Vec<Term>,
+}
+
+impl Query {
+    /// The filters of the query supported by the `/messages` endpoint of the Nylas API.
+    ///
+    /// Only criteria the API evaluates exactly as [`Query::matches`] are included: negated
+    /// criteria, partial addresses, subjects, filenames and free text are left out and must be
+    /// evaluated locally.
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params: Vec<(&'static str, String)> = Vec::new();
+
+        for term in self.terms.iter().filter(|term| !term.negated) {
+            let param = match &term.criterion {
+                Criterion::From(email) if is_email(email) => ("from", email.clone()),
+                Criterion::To(email) if is_email(email) => ("to", email.clone()),
+                Criterion::Cc(email) if is_email(email) => ("cc", email.clone()),
+                Criterion::Bcc(email) if is_email(email) => ("bcc", email.clone()),
+                Criterion::In(container) => ("in", container.clone()),
+                Criterion::Thread(id) => ("thread_id", id.clone()),
+                Criterion::Unread(unread) => ("unread", unread.to_string()),
+                Criterion::Starred(starred) => ("starred", starred.to_string()),
+                Criterion::HasAttachment => ("has_attachment", "true".to_string()),
+                // Nylas excludes the timestamp itself
+                Criterion::After(timestamp) => {
+                    ("received_after", timestamp.saturating_sub(1).to_string())
+                }
+                Criterion::Before(timestamp) => ("received_before", timestamp.to_string()),
+                _ => continue,
+            };
+
+            // The API accepts each parameter once, the other occurrences are checked locally
+            if params.iter().all(|(name, _)| *name != param.0) {
+                params.push(param);
+            }
+        }
+
+        params
+    }
+
+    /// Check whether a message matches every term of the query.
+    ///
+    /// Text comparisons are case-insensitive.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nylas::messages::Message;
+    /// use nylas::query::Query;
+    ///
+    /// let message: Message = serde_json::from_str(r#"{
+    ///     "id": "m1", "object": "message", "account_id": "a1", "thread_id": "t1",
+    ///     "subject": "Invoice #42", "from": [{"email": "alice@example.com", "name": "Alice"}],
+    ///     "to": [], "cc": [], "bcc": [], "reply_to": [], "date": 1704153600,
+    ///     "unread": true, "starred": false, "snippet": "", "body": "Please find it attached.",
+    ///     "files": [], "events": [], "folder": null, "labels": [], "headers": null
+    /// }"#).unwrap();
+    ///
+    /// let query: Query = "from:alice invoice is:unread -has:attachment".parse().unwrap();
+    /// assert!(query.matches(&message));
+    ///
+    /// let query: Query = r#"from:alice "find it attached" before:2024-01-01"#.parse().unwrap();
+    /// assert!(!query.matches(&message));
+    /// ```
+    pub fn matches(&self, message: &Message) -> bool {
+        self.terms
+            .iter()
+            .all(|term| term.negated != term.criterion.matches(message))
+    }
+}
+
+impl Criterion {
+    fn matches(&self, message: &Message) -> bool {
+        let contains =
+            |haystack: &str, needle: &str| haystack.to_lowercase().contains(&needle.to_lowercase());
+        // A full address is sent to the API, which matches it exactly
+        let any_address = |addresses: &[EmailAddress], value: &str| {
+            if is_email(value) {
+                return addresses
+                    .iter()
+                    .any(|address| address.email.eq_ignore_ascii_case(value));
+            }
+            addresses.iter().any(|address| {
+                contains(&address.email, value)
+                    || address
+                        .name
+                        .as_deref()
+                        .is_some_and(|name| contains(name, value))
+            })
+        };
+
+        match self {
+            Criterion::From(value) => any_address(&message.from, value),
+            Criterion::To(value) => any_address(&message.to, value),
+            Criterion::Cc(value) => any_address(&message.cc, value),
+            Criterion::Bcc(value) => any_address(&message.bcc, value),
+            Criterion::Subject(value) => contains(&message.subject, value),
+            Criterion::In(value) => {
+                let name = value.to_lowercase();
+                let folder = message.folder.iter().any(|folder| {
+                    folder.id == *value
+                        || folder.display_name.to_lowercase() == name
+                        || folder.name.as_deref() == Some(name.as_str())
+                });
+                let label = message.labels.iter().any(|label| {
+                    label.id == *value
+                        || label.display_name.to_lowercase() == name
+                        || label.name == name
+                });
+                folder || label
+            }
+            Criterion::Filename(value) => message.files.iter().any(|file| {
+                file.filename
+                    .as_deref()
+                    .is_some_and(|filename| contains(filename, value))
+            }),
+            Criterion::Thread(id) => message.thread_id == *id,
+            Criterion::Unread(unread) => message.unread == *unread,
+            Criterion::Starred(starred) => message.starred == *starred,
+            Criterion::HasAttachment => !message.files.is_empty(),
+            Criterion::After(timestamp) => message.date >= *timestamp,
+            Criterion::Before(timestamp) => message.date < *timestamp,
+            Criterion::Text(value) => {
+                contains(&message.subject, value)
+                    || contains(&message.snippet, value)
+                    || contains(&message.body, value)
+            }
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut terms = Vec::new();
+
+        for token in tokenize(s)? {
+            let error = |message: String| {
+                format!("Invalid query at position {}: {}", token.position, message)
+            };
+
+            let (operator, name, value) = match &token.operator {
+                Some((operator, name)) => (*operator, name.to_lowercase(), token.value.clone()),
+                None if token.value == "OR" && !token.quoted => {
+                    return Err(error("`OR` is not supported".to_string()));
+                }
+                None => {
+                    terms.push(Term {
+                        negated: token.negated,
+                        criterion: Criterion::Text(token.value.clone()),
+                    });
+                    continue;
+                }
+            };
+
+            if value.is_empty() {
+                return Err(error(format!("missing value after `{}:`", name)));
+            }
+
+            let criterion = match operator {
+                Operator::From => Criterion::From(value),
+                Operator::To => Criterion::To(value),
+                Operator::Cc => Criterion::Cc(value),
+                Operator::Bcc => Criterion::Bcc(value),
+                Operator::Subject => Criterion::Subject(value),
+                Operator::In => Criterion::In(value),
+                Operator::Filename => Criterion::Filename(value),
+                Operator::Thread => Criterion::Thread(value),
+                Operator::Is => match value.to_lowercase().as_str() {
+                    "unread" => Criterion::Unread(true),
+                    "read" => Criterion::Unread(false),
+                    "starred" => Criterion::Starred(true),
+                    "unstarred" => Criterion::Starred(false),
+                    other => {
+                        return Err(error(format!(
+                            "unknown value `is:{}`, expected unread, read, starred or unstarred",
+                            other
+                        )))
+                    }
+                },
+                Operator::Has => match value.to_lowercase().as_str() {
+                    "attachment" => Criterion::HasAttachment,
+                    other => {
+                        return Err(error(format!(
+                            "unknown value `has:{}`, expected attachment",
+                            other
+                        )))
+                    }
+                },
+                Operator::After => Criterion::After(parse_date(&value).map_err(error)?),
+                Operator::Before => Criterion::Before(parse_date(&value).map_err(error)?),
+            };
+
+            terms.push(Term {
+                negated: token.negated,
+                criterion,
+            });
+        }
+
+        Ok(Query { terms })
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Quote the values which would be read back as something else
+        let quote = |value: &str| {
+            if value.is_empty()
+                || value == "OR"
+                || value.starts_with('-')
+                || value.contains(|c: char| c.is_whitespace() || c == ':' || c == '"')
+            {
+                let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+                format!("\"{}\"", escaped)
+            } else {
+                value.to_string()
+            }
+        };
+
+        for (index, term) in self.terms.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            if term.negated {
+                write!(f, "-")?;
+            }
+            match &term.criterion {
+                Criterion::From(value) => write!(f, "from:{}", quote(value))?,
+                Criterion::To(value) => write!(f, "to:{}", quote(value))?,
+                Criterion::Cc(value) => write!(f, "cc:{}", quote(value))?,
+                Criterion::Bcc(value) => write!(f, "bcc:{}", quote(value))?,
+                Criterion::Subject(value) => write!(f, "subject:{}", quote(value))?,
+                Criterion::In(value) => write!(f, "in:{}", quote(value))?,
+                Criterion::Filename(value) => write!(f, "filename:{}", quote(value))?,
+                Criterion::Thread(value) => write!(f, "thread:{}", quote(value))?,
+                Criterion::Unread(true) => write!(f, "is:unread")?,
+                Criterion::Unread(false) => write!(f, "is:read")?,
+                Criterion::Starred(true) => write!(f, "is:starred")?,
+                Criterion::Starred(false) => write!(f, "is:unstarred")?,
+                Criterion::HasAttachment => write!(f, "has:attachment")?,
+                Criterion::After(timestamp) => write!(f, "after:{}", timestamp)?,
+                Criterion::Before(timestamp) => write!(f, "before:{}", timestamp)?,
+                Criterion::Text(value) => write!(f, "{}", quote(value))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operator {
+    From,
+    To,
+    Cc,
+    Bcc,
+    Subject,
+    In,
+    Filename,
+    Thread,
+    Is,
+    Has,
+    After,
+    Before,
+}
+
+impl Operator {
+    // The operator named `name`, whatever its case
+    fn from_name(name: &str) -> Option<Self> {
+        let operator = match name.to_lowercase().as_str() {
+            "from" => Operator::From,
+            "to" => Operator::To,
+            "cc" => Operator::Cc,
+            "bcc" => Operator::Bcc,
+            "subject" => Operator::Subject,
+            "in" | "label" => Operator::In,
+            "filename" => Operator::Filename,
+            "thread" => Operator::Thread,
+            "is" => Operator::Is,
+            "has" => Operator::Has,
+            "after" => Operator::After,
+            "before" => Operator::Before,
+            _ => return None,
+        };
+        Some(operator)
+    }
+}
+
+struct Token {
+    position: usize,
+    negated: bool,
+    /// The operator and its name as written.
+    operator: Option<(Operator, String)>,
+    value: String,
+    quoted: bool,
+}
+
+// Split a query into `[-][operator:]value` tokens, values being words or quoted phrases. A colon
+// which doesn't follow the name of an `Operator` is part of the value. In quoted phrases, a
+// backslash escapes a quote or a backslash and is kept as is before any other character.
+fn tokenize(query: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut token = Token {
+            position,
+            negated: false,
+            operator: None,
+            value: String::new(),
+            quoted: false,
+        };
+        if c == '-' {
+            token.negated = true;
+            chars.next();
+        }
+
+        loop {
+            match chars.peek() {
+                Some(&(_, '"')) => {
+                    chars.next();
+                    token.quoted = true;
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break,
+                            Some((_, '\\')) => match chars.peek() {
+                                Some(&(_, c @ ('"' | '\\'))) => {
+                                    chars.next();
+                                    token.value.push(c);
+                                }
+                                _ => token.value.push('\\'),
+                            },
+                            Some((_, c)) => token.value.push(c),
+                            None => {
+                                return Err(format!(
+                                    "Invalid query at position {}: unterminated quote",
+                                    position
+                                ))
+                            }
+                        }
+                    }
+                }
+                Some(&(_, ':')) if token.operator.is_none() && !token.quoted => {
+                    chars.next();
+                    match Operator::from_name(&token.value) {
+                        Some(operator) => {
+                            token.operator = Some((operator, std::mem::take(&mut token.value)));
+                        }
+                        None => token.value.push(':'),
+                    }
+                }
+                Some(&(_, c)) if !c.is_whitespace() => {
+                    chars.next();
+                    token.value.push(c);
+                }
+                _ => break,
+            }
+        }
+
+        if token.operator.is_none() && token.value.is_empty() && !token.quoted {
+            return Err(format!(
+                "Invalid query at position {}: missing criterion after `-`",
+                position
+            ));
+        }
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn is_email(value: &str) -> bool {
+    match value.split_once('@') {
+        Some((local, domain)) => !local.is_empty() && domain.contains('.'),
+        None => false,
+    }
+}
+
+// Parse `YYYY/MM/DD`, `YYYY-MM-DD` (midnight UTC, years 1 to 9999) or a Unix timestamp, negative
+// before 1970.
+fn parse_date(value: &str) -> Result<i64, String> {
+    let invalid = || format!("invalid date `{}`, expected YYYY/MM/DD", value);
+
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+        return value.parse().map_err(|_| invalid());
+    }
+
+    let parts: Vec<&str> = value.split(['/', '-']).collect();
+    let [year, month, day] = parts[..] else {
+        return Err(invalid());
+    };
+    let year: i64 = year.parse().map_err(|_| invalid())?;
+    let month: i64 = month.parse().map_err(|_| invalid())?;
+    let day: i64 = day.parse().map_err(|_| invalid())?;
+    if !(1..=9999).contains(&year) {
+        return Err(invalid());
+    }
+
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(invalid()),
+    };
+    if day < 1 || day > days_in_month {
+        return Err(invalid());
+    }
+
+    // Days since the Unix epoch of a proleptic Gregorian date
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Ok(days * 86_400)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(query: &str) -> Result<Vec<Term>, String> {
+        query.parse::<Query>().map(|query| query.terms)
+    }
+
+    fn term(negated: bool, criterion: Criterion) -> Term {
+        Term { negated, criterion }
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(parse_date("1970/01/01"), Ok(0));
+        assert_eq!(parse_date("2024-01-01"), Ok(1_704_067_200));
+        assert_eq!(parse_date("1704067200"), Ok(1_704_067_200));
+        assert_eq!(parse_date("1969/12/31"), Ok(-86_400));
+        assert_eq!(parse_date("9999/12/31"), Ok(253_402_214_400));
+    }
+
+    #[test]
+    fn leap_days() {
+        assert_eq!(parse_date("2024/02/29"), Ok(1_709_164_800));
+        assert_eq!(parse_date("2000/02/29"), Ok(951_782_400));
+        assert!(parse_date("2023/02/29").is_err());
+        assert!(parse_date("1900/02/29").is_err());
+    }
+
+    #[test]
+    fn invalid_months_and_days() {
+        for date in [
+            "2024/00/10",
+            "2024/13/10",
+            "2024/04/31",
+            "2024/01/00",
+            "2024/01/32",
+            "2024/1",
+            "2024/01/01/01",
+            "2024/a/01",
+            "",
+        ] {
+            assert_eq!(
+                parse_date(date),
+                Err(format!("invalid date `{}`, expected YYYY/MM/DD", date)),
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_range_years_are_rejected_instead_of_overflowing() {
+        for date in [
+            "100000000000000/01/01",
+            "-100000000000000/01/01",
+            "10000/01/01",
+            "0/01/01",
+            "99999999999999999999999/01/01",
+            "99999999999999999999999",
+        ] {
+            assert!(parse_date(date).is_err(), "{}", date);
+        }
+        assert_eq!(
+            parse("after:100000000000000/01/01").unwrap_err(),
+            "Invalid query at position 0: invalid date `100000000000000/01/01`, expected YYYY/MM/DD"
+        );
+    }
+
+    #[test]
+    fn negated_terms() {
+        assert_eq!(
+            parse("-from:noreply@example.com -is:unread -\"weekly digest\" -re:x").unwrap(),
+            vec![
+                term(true, Criterion::From("noreply@example.com".to_string())),
+                term(true, Criterion::Unread(true)),
+                term(true, Criterion::Text("weekly digest".to_string())),
+                term(true, Criterion::Text("re:x".to_string())),
+            ]
+        );
+        // Negated criteria are evaluated locally
+        assert!("-from:noreply@example.com"
+            .parse::<Query>()
+            .unwrap()
+            .params()
+            .is_empty());
+    }
+
+    #[test]
+    fn words_with_colons_are_text() {
+        assert_eq!(
+            parse("10:30 https://example.com/a:b :smile: FROM:alice").unwrap(),
+            vec![
+                term(false, Criterion::Text("10:30".to_string())),
+                term(
+                    false,
+                    Criterion::Text("https://example.com/a:b".to_string())
+                ),
+                term(false, Criterion::Text(":smile:".to_string())),
+                term(false, Criterion::From("alice".to_string())),
+            ]
+        );
+        assert_eq!(
+            parse("from:https://x").unwrap(),
+            vec![term(false, Criterion::From("https://x".to_string()))]
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(
+            parse("is:unread from:").unwrap_err(),
+            "Invalid query at position 10: missing value after `from:`"
+        );
+        assert_eq!(
+            parse("LABEL:").unwrap_err(),
+            "Invalid query at position 0: missing value after `label:`"
+        );
+        assert_eq!(
+            parse("is:maybe").unwrap_err(),
+            "Invalid query at position 0: unknown value `is:maybe`, expected unread, read, \
+             starred or unstarred"
+        );
+        assert_eq!(
+            parse("has:link").unwrap_err(),
+            "Invalid query at position 0: unknown value `has:link`, expected attachment"
+        );
+        assert_eq!(
+            parse("a OR b").unwrap_err(),
+            "Invalid query at position 2: `OR` is not supported"
+        );
+        assert_eq!(
+            parse("subject:\"open").unwrap_err(),
+            "Invalid query at position 0: unterminated quote"
+        );
+        assert_eq!(
+            parse("a - b").unwrap_err(),
+            "Invalid query at position 2: missing criterion after `-`"
+        );
+        assert_eq!(
+            parse("\"OR\"").unwrap(),
+            vec![term(false, Criterion::Text("OR".to_string()))]
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for query in [
+            "from:alice is:unread has:attachment",
+            r#"-subject:"quarterly report" in:inbox after:1704067200"#,
+            r#""re:invoice" -is:starred"#,
+        ] {
+            let parsed: Query = query.parse().unwrap();
+            assert_eq!(parsed.to_string(), query);
+            assert_eq!(parsed.to_string().parse::<Query>().unwrap(), parsed);
+        }
+    }
+
+    #[test]
+    fn display_quotes_values_which_would_be_misread() {
+        let query = Query {
+            terms: vec![
+                term(false, Criterion::Text("OR".to_string())),
+                term(false, Criterion::Text(r#"say "hi""#.to_string())),
+                term(false, Criterion::Subject(r#"a\"b"#.to_string())),
+                term(false, Criterion::Text(r"C:\path\".to_string())),
+                term(false, Criterion::Text("-draft".to_string())),
+                term(false, Criterion::Text(String::new())),
+                term(true, Criterion::Text("is:unread".to_string())),
+                term(false, Criterion::After(-86_400)),
+                term(false, Criterion::Before(i64::MIN)),
+            ],
+        };
+        assert_eq!(
+            query.to_string(),
+            r#""OR" "say \"hi\"" subject:"a\\\"b" "C:\\path\\" "-draft" "" -"is:unread" after:-86400 before:-9223372036854775808"#
+        );
+        assert_eq!(query.to_string().parse::<Query>().unwrap(), query);
+    }
+
+    #[test]
+    fn backslashes_escape_only_quotes_and_backslashes() {
+        assert_eq!(
+            parse(r#""a \"b\" \\ \n" c\d"#).unwrap(),
+            vec![
+                term(false, Criterion::Text(r#"a "b" \ \n"#.to_string())),
+                term(false, Criterion::Text(r"c\d".to_string())),
+            ]
+        );
+    }
+
+    fn message(from: &str) -> Message {
+        serde_json::from_value(serde_json::json!({
+            "id": "m1", "object": "message", "account_id": "a1", "thread_id": "t1",
+            "subject": "", "from": [{"email": from, "name": "Alice"}], "to": [], "cc": [],
+            "bcc": [], "reply_to": [], "date": 0, "unread": false, "starred": false,
+            "snippet": "", "body": "", "files": [], "events": [], "folder": null, "labels": [],
+            "headers": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn full_addresses_match_locally_like_the_api_filter() {
+        // Sent to the API, which only returns messages from this exact address
+        let query: Query = "from:alice@example.com".parse().unwrap();
+        assert_eq!(
+            query.params(),
+            vec![("from", "alice@example.com".to_string())]
+        );
+        assert!(query.matches(&message("alice@example.com")));
+        assert!(query.matches(&message("Alice@Example.com")));
+        assert!(!query.matches(&message("malice@example.com")));
+        assert!(!query.matches(&message("alice@example.com.evil")));
+
+        // Evaluated locally only, on parts of addresses and names
+        let query: Query = "from:alice".parse().unwrap();
+        assert!(query.params().is_empty());
+        assert!(query.matches(&message("malice@example.com")));
+        assert!(query.matches(&message("bob@example.com")));
+    }
+
+    #[test]
+    fn params_keep_the_first_occurrence() {
+        let query: Query = "in:inbox in:work before:2024/01/01 from:alice"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            query.params(),
+            vec![
+                ("in", "inbox".to_string()),
+                ("received_before", "1704067200".to_string()),
+            ]
+        );
+    }
+}
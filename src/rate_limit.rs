@@ -0,0 +1,244 @@
+//! Rate-limit awareness.
+//!
+//! Nylas answers `429 Too Many Requests` when an application or an account sends too many
+//! requests. Such responses are retried by the [`RetryPolicy`](crate::retry::RetryPolicy) of the
+//! client once the delay given by the `Retry-After` header elapsed, and the rate-limit headers of
+//! the last response are available with `Nylas::rate_limit`.
+//!
+//! A [`RateLimiter`] can also be attached to the client to stay under the quota proactively,
+//! e.g. during large backfills.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nylas::client::Nylas;
+//! use nylas::rate_limit::RateLimiter;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let access_token = "YOUR_ACCESS_TOKEN";
+//!
+//!     // At most 10 requests per second for each account
+//!     let limiter = Arc::new(RateLimiter::new(10, Duration::from_secs(1)));
+//!     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", Some(access_token))
+//!         .await
+//!         .unwrap()
+//!         .with_rate_limiter(limiter);
+//!
+//!     let messages = nylas.messages().all().await;
+//!     if let Some(rate_limit) = nylas.rate_limit() {
+//!         println!("{:?} requests left", rate_limit.remaining);
+//!     }
+//! }
+//! ```
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Header holding the number of requests allowed in the current window.
+pub const LIMIT_HEADER: &str = "X-RateLimit-Limit";
+/// Header holding the number of requests left in the current window.
+pub const REMAINING_HEADER: &str = "X-RateLimit-Remaining";
+/// Header holding the number of seconds until the current window resets.
+pub const RESET_HEADER: &str = "X-RateLimit-Reset";
+
+/// The rate-limit information sent with a response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    pub reset: Option<Duration>,
+    /// The delay to wait before sending another request, sent with `429` responses.
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimit {
+    /// Read the rate-limit headers of a response, or `None` if there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nylas::rate_limit::RateLimit;
+    /// use reqwest::header::HeaderMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("X-RateLimit-Limit", "120".parse().unwrap());
+    /// headers.insert("X-RateLimit-Remaining", "0".parse().unwrap());
+    /// headers.insert("Retry-After", "30".parse().unwrap());
+    ///
+    /// let rate_limit = RateLimit::from_headers(&headers).unwrap();
+    /// assert_eq!(rate_limit.limit, Some(120));
+    /// assert_eq!(rate_limit.remaining, Some(0));
+    /// assert_eq!(rate_limit.retry_after, Some(Duration::from_secs(30)));
+    /// ```
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let number = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+        };
+
+        let rate_limit = RateLimit {
+            limit: number(LIMIT_HEADER),
+            remaining: number(REMAINING_HEADER),
+            reset: number(RESET_HEADER).map(Duration::from_secs),
+            retry_after: retry_after(headers),
+        };
+
+        if rate_limit == RateLimit::default() {
+            None
+        } else {
+            Some(rate_limit)
+        }
+    }
+}
+
+/// Parse the `Retry-After` header, given either in seconds or as an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// A client-side token-bucket limiter, with one bucket per account.
+///
+/// Each bucket holds up to `capacity` requests and is refilled continuously at `capacity`
+/// requests per `period`. Share a limiter between several `Nylas` instances with an `Arc` to
+/// apply a single quota to all of them. Buckets which refilled are dropped, so keys which are no
+/// longer used don't accumulate.
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_second: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `capacity` requests per `period` for each account.
+    pub fn new(capacity: u32, period: Duration) -> Self {
+        let capacity = f64::from(capacity.max(1));
+        RateLimiter {
+            capacity,
+            refill_per_second: capacity / period.as_secs_f64().max(f64::EPSILON),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token from the bucket of `key` if one is available.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the request can be sent now, or the delay until a token is available.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nylas::rate_limit::RateLimiter;
+    /// use std::time::Duration;
+    ///
+    /// let limiter = RateLimiter::new(2, Duration::from_secs(60));
+    ///
+    /// assert!(limiter.try_acquire("account").is_ok());
+    /// assert!(limiter.try_acquire("account").is_ok());
+    /// assert!(limiter.try_acquire("account").is_err());
+    ///
+    /// // Other accounts have their own bucket
+    /// assert!(limiter.try_acquire("other account").is_ok());
+    /// ```
+    pub fn try_acquire(&self, key: &str) -> Result<(), Duration> {
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        if !buckets.contains_key(key) {
+            // A full bucket is the same as a new one
+            buckets.retain(|_, bucket| !self.is_full(bucket, now));
+        }
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(
+                Duration::try_from_secs_f64((1.0 - bucket.tokens) / self.refill_per_second)
+                    .unwrap_or(Duration::MAX),
+            )
+        }
+    }
+
+    fn is_full(&self, bucket: &Bucket, now: Instant) -> bool {
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens + elapsed * self.refill_per_second >= self.capacity
+    }
+
+    /// Wait until a token is available in the bucket of `key`, and take it.
+    pub async fn acquire(&self, key: &str) {
+        while let Err(delay) = self.try_acquire(key) {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buckets(limiter: &RateLimiter) -> usize {
+        limiter.buckets.lock().unwrap().len()
+    }
+
+    #[test]
+    fn delays_too_long_to_be_represented_are_capped() {
+        let limiter = RateLimiter::new(1, Duration::MAX);
+        assert!(limiter.try_acquire("account").is_ok());
+        assert_eq!(limiter.try_acquire("account"), Err(Duration::MAX));
+    }
+
+    #[test]
+    fn refilled_buckets_are_dropped() {
+        let limiter = RateLimiter::new(1, Duration::from_millis(10));
+        assert!(limiter.try_acquire("a").is_ok());
+        assert!(limiter.try_acquire("b").is_ok());
+        assert_eq!(buckets(&limiter), 2);
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(limiter.try_acquire("c").is_ok());
+        assert_eq!(buckets(&limiter), 1);
+    }
+
+    #[test]
+    fn buckets_in_use_are_kept() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        assert!(limiter.try_acquire("a").is_ok());
+        assert!(limiter.try_acquire("b").is_ok());
+        assert_eq!(buckets(&limiter), 2);
+        assert!(limiter.try_acquire("a").is_err());
+    }
+}
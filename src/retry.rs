@@ -0,0 +1,419 @@
+//! Automatic retries of failed requests.
+//!
+//! Every request sent by a `Nylas` instance goes through its [`RetryPolicy`]. Connection
+//! failures and transient server errors (`500`, `502`, `503`, `504` by default) are retried with
+//! an exponential backoff and jitter, up to a maximum number of attempts.
+//!
+//! Only idempotent requests are retried once they may have reached Nylas: `GET`, `HEAD`,
+//! `OPTIONS`, `PUT` and `DELETE` requests, and `POST` or `PATCH` requests carrying an
+//! [`IDEMPOTENCY_KEY_HEADER`]. Other requests are only retried when the connection couldn't be
+//! established, since Nylas never saw them.
+//!
+//! Rate-limited requests (`429 Too Many Requests`) weren't processed by Nylas, so they are
+//! retried whatever their method, after the delay given by the `Retry-After` header.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nylas::client::Nylas;
+//! use nylas::retry::RetryPolicy;
+//! use std::time::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let access_token = "YOUR_ACCESS_TOKEN";
+//!     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", Some(access_token))
+//!         .await
+//!         .unwrap()
+//!         .with_retry_policy(
+//!             RetryPolicy::default()
+//!                 .with_max_attempts(5)
+//!                 .with_backoff(Duration::from_millis(500), Duration::from_secs(30)),
+//!         );
+//! }
+//! ```
+
+use crate::rate_limit::{self, RateLimiter};
+use reqwest::{Client, Method, Request, Response, StatusCode};
+use std::time::Duration;
+
+/// Header marking a `POST` or `PATCH` request as safe to retry.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Describes when and how failed requests are retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one. `1` disables retries.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    pub initial_backoff: Duration,
+    /// The upper bound of the delay between two attempts.
+    pub max_backoff: Duration,
+    /// The factor applied to the delay after every attempt. Delays which can't be computed, e.g.
+    /// with a `NaN` multiplier, fall back to `max_backoff`.
+    pub multiplier: f64,
+    /// Whether to pick a random delay between zero and the computed backoff ("full jitter"),
+    /// which spreads the retries of concurrent clients.
+    pub jitter: bool,
+    /// The response statuses worth retrying.
+    pub retryable_statuses: Vec<StatusCode>,
+    /// The methods considered idempotent.
+    pub idempotent_methods: Vec<Method>,
+    /// Whether to retry `429 Too Many Requests` responses.
+    pub retry_rate_limited: bool,
+    /// The longest `Retry-After` delay to wait for. Rate-limited responses asking for a longer
+    /// delay are returned as is.
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: true,
+            retryable_statuses: vec![
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            idempotent_methods: vec![
+                Method::GET,
+                Method::HEAD,
+                Method::OPTIONS,
+                Method::PUT,
+                Method::DELETE,
+            ],
+            retry_rate_limited: true,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy sending every request only once.
+    pub fn never() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Set the factor applied to the delay after every attempt. Factors below `1`, infinite or
+    /// `NaN` are replaced by `1`, i.e. a constant delay.
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = if multiplier.is_finite() && multiplier >= 1.0 {
+            multiplier
+        } else {
+            1.0
+        };
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The delay to wait after the given failed attempt, starting at `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nylas::retry::RetryPolicy;
+    /// use std::time::Duration;
+    ///
+    /// let policy = RetryPolicy::default()
+    ///     .with_backoff(Duration::from_millis(100), Duration::from_millis(300))
+    ///     .with_jitter(false);
+    ///
+    /// assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    /// assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    /// assert_eq!(policy.backoff(3), Duration::from_millis(300));
+    /// ```
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let max_backoff = self.max_backoff.as_secs_f64();
+        let delay = if delay.is_nan() {
+            max_backoff
+        } else {
+            delay.clamp(0.0, max_backoff)
+        };
+
+        let delay = if self.jitter {
+            delay * fastrand::f64()
+        } else {
+            delay
+        };
+        Duration::try_from_secs_f64(delay).unwrap_or(self.max_backoff)
+    }
+
+    /// Whether a request may be sent again after it reached Nylas.
+    pub fn is_idempotent(&self, request: &reqwest::Request) -> bool {
+        self.idempotent_methods.contains(request.method())
+            || request.headers().contains_key(IDEMPOTENCY_KEY_HEADER)
+    }
+
+    /// Send a request, retrying it according to the policy.
+    ///
+    /// The last response is returned whatever its status, so callers handle unsuccessful
+    /// responses as usual. Requests whose body can't be cloned are sent only once. When a
+    /// limiter is given, every attempt waits for a token of the bucket of the given key.
+    ///
+    /// Returns the outcome of the last attempt along with the number of retries.
+    pub(crate) async fn execute(
+        &self,
+        client: &Client,
+        request: Request,
+        idempotent: bool,
+        limiter: Option<(&RateLimiter, &str)>,
+    ) -> (Result<Response, reqwest::Error>, u32) {
+        let idempotent = idempotent || self.is_idempotent(&request);
+
+        let mut attempt = 1;
+        loop {
+            if let Some((limiter, key)) = limiter {
+                limiter.acquire(key).await;
+            }
+
+            let retry = match request.try_clone() {
+                Some(retry) if attempt < self.max_attempts => retry,
+                _ => return (client.execute(request).await, attempt - 1),
+            };
+
+            let delay = match client.execute(retry).await {
+                Ok(response)
+                    if self.retry_rate_limited
+                        && response.status() == StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    let delay = match rate_limit::retry_after(response.headers()) {
+                        Some(delay) if delay > self.max_retry_after => {
+                            return (Ok(response), attempt - 1)
+                        }
+                        Some(delay) => delay,
+                        None => self.backoff(attempt),
+                    };
+
+                    // The last response is recorded with the outcome of the request, only the
+                    // responses which are retried are recorded here
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::record_rate_limited(request.method(), request.url());
+
+                    delay
+                }
+                Ok(response)
+                    if idempotent && self.retryable_statuses.contains(&response.status()) =>
+                {
+                    self.backoff(attempt)
+                }
+                Ok(response) => return (Ok(response), attempt - 1),
+                // The request never reached Nylas
+                Err(error) if error.is_connect() => self.backoff(attempt),
+                Err(error) if idempotent && (error.is_timeout() || error.is_request()) => {
+                    self.backoff(attempt)
+                }
+                Err(error) => return (Err(error), attempt - 1),
+            };
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "retrying request"
+            );
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{respond, respond_with, serve};
+    use std::net::{SocketAddr, TcpListener};
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::default()
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(5))
+            .with_jitter(false)
+    }
+
+    fn ok() -> String {
+        respond("200 OK", "")
+    }
+
+    fn unavailable() -> String {
+        respond("503 Service Unavailable", "")
+    }
+
+    fn rate_limited(retry_after: &str) -> String {
+        respond_with("429 Too Many Requests", &[("retry-after", retry_after)], "")
+    }
+
+    fn url(addr: SocketAddr) -> String {
+        format!("http://{}/messages", addr)
+    }
+
+    async fn send(policy: &RetryPolicy, method: Method, url: &str) -> (Option<StatusCode>, u32) {
+        let client = Client::new();
+        let request = client.request(method, url).build().unwrap();
+        let (result, retries) = policy.execute(&client, request, false, None).await;
+        (result.ok().map(|response| response.status()), retries)
+    }
+
+    #[test]
+    fn backoff_grows_up_to_the_maximum() {
+        let policy = RetryPolicy::default()
+            .with_backoff(Duration::from_millis(100), Duration::from_secs(1))
+            .with_jitter(false);
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn backoff_never_panics_on_invalid_multipliers() {
+        for multiplier in [
+            f64::NAN,
+            -2.0,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            0.0,
+            f64::MAX,
+        ] {
+            let mut policy = policy();
+            policy.multiplier = multiplier;
+            for attempt in [1, 2, 3, 1000, u32::MAX] {
+                assert!(policy.backoff(attempt) <= policy.max_backoff);
+                assert!(policy.clone().with_jitter(true).backoff(attempt) <= policy.max_backoff);
+            }
+        }
+
+        let mut policy = policy();
+        policy.multiplier = f64::NAN;
+        assert_eq!(policy.backoff(2), policy.max_backoff);
+        policy.multiplier = -2.0;
+        assert_eq!(policy.backoff(2), Duration::ZERO);
+    }
+
+    #[test]
+    fn builders_validate_their_values() {
+        assert_eq!(RetryPolicy::default().with_max_attempts(0).max_attempts, 1);
+        assert_eq!(
+            RetryPolicy::default().with_multiplier(f64::NAN).multiplier,
+            1.0
+        );
+        assert_eq!(RetryPolicy::default().with_multiplier(-3.0).multiplier, 1.0);
+        assert_eq!(RetryPolicy::default().with_multiplier(1.5).multiplier, 1.5);
+    }
+
+    #[test]
+    fn jitter_stays_below_the_backoff() {
+        let policy = policy().with_jitter(true);
+        for attempt in 1..100 {
+            assert!(policy.backoff(attempt) <= Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn idempotency() {
+        let client = Client::new();
+        let policy = RetryPolicy::default();
+        let request = |method| client.request(method, "http://localhost/").build().unwrap();
+
+        assert!(policy.is_idempotent(&request(Method::GET)));
+        assert!(policy.is_idempotent(&request(Method::DELETE)));
+        assert!(!policy.is_idempotent(&request(Method::POST)));
+
+        let keyed = client
+            .post("http://localhost/")
+            .header(IDEMPOTENCY_KEY_HEADER, "key")
+            .build()
+            .unwrap();
+        assert!(policy.is_idempotent(&keyed));
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried() {
+        let (addr, received) = serve(vec![unavailable(), unavailable(), ok()]);
+        assert_eq!(
+            send(&policy(), Method::GET, &url(addr)).await,
+            (Some(StatusCode::OK), 2)
+        );
+        assert_eq!(received.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn the_last_response_is_returned_once_attempts_are_exhausted() {
+        let (addr, received) = serve(vec![unavailable(), unavailable(), unavailable()]);
+        assert_eq!(
+            send(&policy(), Method::GET, &url(addr)).await,
+            (Some(StatusCode::SERVICE_UNAVAILABLE), 2)
+        );
+        assert_eq!(received.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn non_idempotent_requests_are_not_retried_after_reaching_the_server() {
+        let (addr, received) = serve(vec![unavailable(), ok()]);
+        assert_eq!(
+            send(&policy(), Method::POST, &url(addr)).await,
+            (Some(StatusCode::SERVICE_UNAVAILABLE), 0)
+        );
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_requests_are_retried_whatever_their_method() {
+        let (addr, _) = serve(vec![rate_limited("0"), ok()]);
+        assert_eq!(
+            send(&policy(), Method::POST, &url(addr)).await,
+            (Some(StatusCode::OK), 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn long_retry_after_delays_are_not_waited_for() {
+        let (addr, received) = serve(vec![rate_limited("3600"), ok()]);
+        assert_eq!(
+            send(&policy(), Method::GET, &url(addr)).await,
+            (Some(StatusCode::TOO_MANY_REQUESTS), 0)
+        );
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn connection_failures_are_retried_then_returned() {
+        // Nothing listens on the port once the listener is dropped
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let url = format!("http://127.0.0.1:{}/", port);
+
+        let (status, retries) = send(&policy(), Method::POST, &url).await;
+        assert_eq!((status, retries), (None, 2));
+
+        let (status, retries) = send(&RetryPolicy::never(), Method::GET, &url).await;
+        assert_eq!((status, retries), (None, 0));
+    }
+}
@@ -0,0 +1,703 @@
+//! Offline full-text search over synced messages.
+//!
+//! [`SearchIndex`] indexes the subject, body (HTML stripped), snippet, participants and
+//! attachment filenames of messages with an embedded [tantivy](https://docs.rs/tantivy) index.
+//! Queries support phrases (`"quarterly report"`), field filters (`subject:invoice`,
+//! `from:alice@example.com`, `filename:pdf`, `unread:true`, `date:[1700000000 TO 1800000000]`)
+//! and boolean operators, and results are ranked by relevance.
+//!
+//! Wrap a [`Store`] in a [`SearchStore`] to keep the index up to date while syncing.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nylas::messages::Message;
+//! use nylas::search::SearchIndex;
+//!
+//! let mut index = SearchIndex::create_in_ram().unwrap();
+//!
+//! let message: Message = serde_json::from_str(r#"{
+//!     "id": "m1", "object": "message", "account_id": "a1", "thread_id": "t1",
+//!     "subject": "Quarterly report", "from": [{"email": "alice@example.com", "name": "Alice"}],
+//!     "to": [{"email": "bob@example.com", "name": null}], "cc": [], "bcc": [], "reply_to": [],
+//!     "date": 1700000000, "unread": true, "starred": false, "snippet": "Numbers are up",
+//!     "body": "<p>The <b>numbers</b> are up this quarter.</p>",
+//!     "files": [{"content_disposition": "attachment", "content_type": "application/pdf",
+//!                "filename": "q3-results.pdf", "id": "f1", "size": 1024}],
+//!     "events": [], "folder": null, "labels": [], "headers": null
+//! }"#).unwrap();
+//! index.index_message(&message).unwrap();
+//! index.commit().unwrap();
+//!
+//! let hits = index.search(r#""numbers are up" AND from:alice AND filename:pdf"#, 10).unwrap();
+//! assert_eq!(hits.len(), 1);
+//! assert_eq!(hits[0].id, "m1");
+//! ```
+
+use crate::deltas::{DeltaObject, ObjectType};
+use crate::messages::Message;
+use crate::sync::{Backfill, Store};
+use std::collections::HashMap;
+use std::path::Path;
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Field, Schema, Value, FAST, INDEXED, STORED, STRING, TEXT};
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+
+const WRITER_MEMORY_BUDGET: usize = 50_000_000;
+
+/// A message matching a search query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub id: String,
+    pub thread_id: String,
+    pub date: i64,
+    /// The relevance of the message, higher is better.
+    pub score: f32,
+}
+
+struct Fields {
+    id: Field,
+    thread_id: Field,
+    subject: Field,
+    body: Field,
+    snippet: Field,
+    from: Field,
+    to: Field,
+    cc: Field,
+    bcc: Field,
+    filename: Field,
+    date: Field,
+    unread: Field,
+    starred: Field,
+}
+
+/// A full-text index of messages.
+pub struct SearchIndex {
+    index: Index,
+    reader: IndexReader,
+    writer: IndexWriter,
+    fields: Fields,
+}
+
+impl SearchIndex {
+    /// Create an index living in memory.
+    pub fn create_in_ram() -> Result<Self, String> {
+        let (schema, fields) = Self::schema();
+        Self::from_index(Index::create_in_ram(schema), fields)
+    }
+
+    /// Open the index stored in `path`, or create it if the directory is empty.
+    pub fn open_or_create<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let (schema, fields) = Self::schema();
+        let directory = MmapDirectory::open(path).map_err(|e| format!("Search Error: {}", e))?;
+        let index =
+            Index::open_or_create(directory, schema).map_err(|e| format!("Search Error: {}", e))?;
+        Self::from_index(index, fields)
+    }
+
+    /// Add a message to the index, replacing its previous version if any.
+    ///
+    /// Changes are only visible to searches after [`SearchIndex::commit`]. The body is indexed
+    /// without its tags, scripts and styles.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nylas::messages::Message;
+    /// use nylas::search::SearchIndex;
+    ///
+    /// let mut index = SearchIndex::create_in_ram().unwrap();
+    ///
+    /// let message: Message = serde_json::from_str(r#"{
+    ///     "id": "m1", "object": "message", "account_id": "a1", "thread_id": "t1",
+    ///     "subject": "Newsletter", "from": [], "to": [], "cc": [], "bcc": [], "reply_to": [],
+    ///     "date": 1700000000, "unread": false, "starred": false, "snippet": "",
+    ///     "body": "<html><head><style type=\"text/css\">p { color: teal; }</style></head><body><p>Welcome aboard</p><styled-box>Pinned</styled-box></body></html>",
+    ///     "files": [], "events": [], "folder": null, "labels": [], "headers": null
+    /// }"#).unwrap();
+    /// index.index_message(&message).unwrap();
+    /// index.commit().unwrap();
+    ///
+    /// assert_eq!(index.search("welcome", 10).unwrap().len(), 1);
+    /// assert_eq!(index.search("pinned", 10).unwrap().len(), 1);
+    /// assert!(index.search("teal", 10).unwrap().is_empty());
+    /// ```
+    pub fn index_message(&mut self, message: &Message) -> Result<(), String> {
+        let fields = &self.fields;
+        self.writer
+            .delete_term(Term::from_field_text(fields.id, &message.id));
+
+        let mut document = TantivyDocument::default();
+        document.add_text(fields.id, &message.id);
+        document.add_text(fields.thread_id, &message.thread_id);
+        document.add_text(fields.subject, &message.subject);
+        document.add_text(fields.body, strip_html(&message.body));
+        document.add_text(fields.snippet, &message.snippet);
+        for (field, addresses) in [
+            (fields.from, &message.from),
+            (fields.to, &message.to),
+            (fields.cc, &message.cc),
+            (fields.bcc, &message.bcc),
+        ] {
+            for address in addresses {
+                document.add_text(field, &address.email);
+                if let Some(name) = &address.name {
+                    document.add_text(field, name);
+                }
+            }
+        }
+        for file in &message.files {
+            if let Some(filename) = &file.filename {
+                document.add_text(fields.filename, filename);
+            }
+        }
+        document.add_i64(fields.date, message.date);
+        document.add_bool(fields.unread, message.unread);
+        document.add_bool(fields.starred, message.starred);
+
+        self.writer
+            .add_document(document)
+            .map(|_| ())
+            .map_err(|e| format!("Search Error: {}", e))
+    }
+
+    /// Remove a message from the index.
+    ///
+    /// Changes are only visible to searches after [`SearchIndex::commit`].
+    pub fn remove_message(&mut self, id: &str) {
+        self.writer
+            .delete_term(Term::from_field_text(self.fields.id, id));
+    }
+
+    /// Persist the pending changes and make them visible to searches.
+    pub fn commit(&mut self) -> Result<(), String> {
+        self.writer
+            .commit()
+            .map_err(|e| format!("Search Error: {}", e))?;
+        self.reader
+            .reload()
+            .map_err(|e| format!("Search Error: {}", e))
+    }
+
+    /// Search the indexed messages.
+    ///
+    /// Terms without a field are looked up in the subject, body, snippet, participants and
+    /// filenames. Matches in the subject and participants weigh more than matches in the body.
+    ///
+    /// # Arguments
+    ///
+    /// - `query`: The query, e.g. `invoice AND from:alice` or `"project kickoff" -unread:true`.
+    /// - `limit`: The maximum number of results.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the matching messages, most relevant first, or an error message if
+    /// the query can't be parsed.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>, String> {
+        let fields = &self.fields;
+        let mut parser = QueryParser::for_index(
+            &self.index,
+            vec![
+                fields.subject,
+                fields.body,
+                fields.snippet,
+                fields.from,
+                fields.to,
+                fields.cc,
+                fields.bcc,
+                fields.filename,
+            ],
+        );
+        parser.set_field_boost(fields.subject, 2.0);
+        parser.set_field_boost(fields.from, 1.5);
+        parser.set_field_boost(fields.to, 1.5);
+
+        let query = parser
+            .parse_query(query)
+            .map_err(|e| format!("Invalid search query: {}", e))?;
+
+        let searcher = self.reader.searcher();
+        let top_docs = searcher
+            .search(&query, &TopDocs::with_limit(limit))
+            .map_err(|e| format!("Search Error: {}", e))?;
+
+        top_docs
+            .into_iter()
+            .map(|(score, address)| {
+                let document: TantivyDocument = searcher
+                    .doc(address)
+                    .map_err(|e| format!("Search Error: {}", e))?;
+                let text = |field| {
+                    document
+                        .get_first(field)
+                        .and_then(|value| value.as_str())
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                Ok(SearchHit {
+                    id: text(fields.id),
+                    thread_id: text(fields.thread_id),
+                    date: document
+                        .get_first(fields.date)
+                        .and_then(|value| value.as_i64())
+                        .unwrap_or_default(),
+                    score,
+                })
+            })
+            .collect()
+    }
+
+    fn schema() -> (Schema, Fields) {
+        let mut builder = Schema::builder();
+        let fields = Fields {
+            id: builder.add_text_field("id", STRING | STORED),
+            thread_id: builder.add_text_field("thread_id", STRING | STORED),
+            subject: builder.add_text_field("subject", TEXT),
+            body: builder.add_text_field("body", TEXT),
+            snippet: builder.add_text_field("snippet", TEXT),
+            from: builder.add_text_field("from", TEXT),
+            to: builder.add_text_field("to", TEXT),
+            cc: builder.add_text_field("cc", TEXT),
+            bcc: builder.add_text_field("bcc", TEXT),
+            filename: builder.add_text_field("filename", TEXT),
+            date: builder.add_i64_field("date", INDEXED | STORED | FAST),
+            unread: builder.add_bool_field("unread", INDEXED),
+            starred: builder.add_bool_field("starred", INDEXED),
+        };
+        (builder.build(), fields)
+    }
+
+    fn from_index(index: Index, fields: Fields) -> Result<Self, String> {
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(|e: tantivy::TantivyError| format!("Search Error: {}", e))?;
+        let writer = index
+            .writer_with_num_threads(1, WRITER_MEMORY_BUDGET)
+            .map_err(|e| format!("Search Error: {}", e))?;
+        Ok(SearchIndex {
+            index,
+            reader,
+            writer,
+            fields,
+        })
+    }
+}
+
+/// A [`Store`] wrapper indexing the messages written to the inner store.
+///
+/// The index is committed when the sync engine flushes the store, once per page of changes.
+/// Until then, the cursors and backfill progress saved while the index has uncommitted changes
+/// are held back from the inner store, so a crash never leaves the inner store ahead of the
+/// index.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use nylas::client::Nylas;
+/// use nylas::search::{SearchIndex, SearchStore};
+/// use nylas::sync::{MemoryStore, SyncEngine};
+/// #[tokio::main]
+/// async fn main() {
+///     let access_token = "YOUR_ACCESS_TOKEN";
+///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", Some(access_token))
+///         .await
+///         .unwrap();
+///
+///     let index = SearchIndex::open_or_create("search-index").unwrap();
+///     let mut engine = SyncEngine::new(SearchStore::new(MemoryStore::new(), index));
+///     engine.sync(&nylas).await.unwrap();
+///
+///     let hits = engine.store().index().search("subject:invoice", 20).unwrap();
+///     for hit in hits {
+///         let message = &engine.store().inner().messages[&hit.id];
+///         println!("{:.2} {}", hit.score, message.subject);
+///     }
+/// }
+/// ```
+pub struct SearchStore<S: Store> {
+    inner: S,
+    index: SearchIndex,
+    dirty: bool,
+    // Saved once the index is committed
+    cursors: HashMap<String, String>,
+    backfills: HashMap<String, Option<Backfill>>,
+}
+
+impl<S: Store> SearchStore<S> {
+    pub fn new(inner: S, index: SearchIndex) -> Self {
+        SearchStore {
+            inner,
+            index,
+            dirty: false,
+            cursors: HashMap::new(),
+            backfills: HashMap::new(),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn index(&self) -> &SearchIndex {
+        &self.index
+    }
+
+    pub fn into_parts(self) -> (S, SearchIndex) {
+        (self.inner, self.index)
+    }
+}
+
+impl<S: Store> Store for SearchStore<S> {
+    fn load_cursor(&self, account_id: &str) -> Result<Option<String>, String> {
+        match self.cursors.get(account_id) {
+            Some(cursor) => Ok(Some(cursor.clone())),
+            None => self.inner.load_cursor(account_id),
+        }
+    }
+
+    fn save_cursor(&mut self, account_id: &str, cursor: &str) -> Result<(), String> {
+        if !self.dirty {
+            return self.inner.save_cursor(account_id, cursor);
+        }
+        self.cursors
+            .insert(account_id.to_string(), cursor.to_string());
+        Ok(())
+    }
+
+    fn load_backfill(&self, account_id: &str) -> Result<Option<Backfill>, String> {
+        match self.backfills.get(account_id) {
+            Some(progress) => Ok(progress.clone()),
+            None => self.inner.load_backfill(account_id),
+        }
+    }
+
+    fn save_backfill(
+        &mut self,
+        account_id: &str,
+        progress: Option<&Backfill>,
+    ) -> Result<(), String> {
+        if !self.dirty {
+            return self.inner.save_backfill(account_id, progress);
+        }
+        self.backfills
+            .insert(account_id.to_string(), progress.cloned());
+        Ok(())
+    }
+
+    fn upsert(&mut self, object: DeltaObject) -> Result<(), String> {
+        // The message is only indexed once the inner store holds it
+        let message = match &object {
+            DeltaObject::Message(message) => Some(message.clone()),
+            _ => None,
+        };
+        self.inner.upsert(object)?;
+        if let Some(message) = message {
+            self.index.index_message(&message)?;
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, object_type: &ObjectType, id: &str) -> Result<(), String> {
+        self.inner.delete(object_type, id)?;
+        if *object_type == ObjectType::Message {
+            self.index.remove_message(id);
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        if self.dirty {
+            self.index.commit()?;
+            self.dirty = false;
+        }
+        for (account_id, cursor) in std::mem::take(&mut self.cursors) {
+            self.inner.save_cursor(&account_id, &cursor)?;
+        }
+        for (account_id, progress) in std::mem::take(&mut self.backfills) {
+            self.inner.save_backfill(&account_id, progress.as_ref())?;
+        }
+        self.inner.flush()
+    }
+}
+
+/// Extract the text of an HTML body, dropping tags, scripts and styles.
+fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find('>') {
+            Some(end) => end,
+            None => {
+                rest = "";
+                break;
+            }
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        // Skip the content of scripts and styles altogether, up to their closing tag
+        if !tag.starts_with('/') {
+            let name = tag
+                .split(|c: char| c.is_ascii_whitespace() || c == '/')
+                .next()
+                .unwrap_or_default();
+            for closing in ["</script", "</style"] {
+                if name.eq_ignore_ascii_case(&closing[2..]) {
+                    rest = match find_ignore_ascii_case(rest, closing) {
+                        Some(position) => &rest[position..],
+                        None => "",
+                    };
+                }
+            }
+        }
+        text.push(' ');
+    }
+    text.push_str(rest);
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+// Find an ASCII `needle` in `haystack`, ignoring the case, without copying the haystack.
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::MemoryStore;
+    use serde_json::json;
+
+    fn message(id: &str, subject: &str, body: &str, date: i64, unread: bool) -> Message {
+        serde_json::from_value(json!({
+            "id": id, "object": "message", "account_id": "a1", "thread_id": format!("t-{}", id),
+            "subject": subject, "from": [{"email": "alice@example.com", "name": "Alice"}],
+            "to": [{"email": "bob@example.com", "name": null}], "cc": [], "bcc": [],
+            "reply_to": [], "date": date, "unread": unread, "starred": false, "snippet": "",
+            "body": body, "files": [], "events": [], "folder": null, "labels": [],
+            "headers": null
+        }))
+        .unwrap()
+    }
+
+    fn index() -> SearchIndex {
+        let mut index = SearchIndex::create_in_ram().unwrap();
+        for message in [
+            message(
+                "m1",
+                "Project kickoff",
+                "<p>Agenda</p>",
+                1_700_000_000,
+                true,
+            ),
+            message(
+                "m2",
+                "Project kickoff notes",
+                "<p>Minutes</p>",
+                1_750_000_000,
+                false,
+            ),
+            message("m3", "Lunch", "<p>Pizza</p>", 1_850_000_000, false),
+        ] {
+            index.index_message(&message).unwrap();
+        }
+        index.commit().unwrap();
+        index
+    }
+
+    fn ids(index: &SearchIndex, query: &str) -> Vec<String> {
+        let mut ids: Vec<String> = index
+            .search(query, 10)
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn strip_html_drops_tags_scripts_and_styles() {
+        assert_eq!(
+            strip_html("<p>Hello&nbsp;<b>world</b></p>").trim(),
+            "Hello  world"
+        );
+        assert_eq!(
+            strip_html("<STYLE>p{}</Style>Kept<script type=\"x\">alert(1)</SCRIPT>too"),
+            "  Kept  too"
+        );
+        assert_eq!(
+            strip_html("<styled-x>Kept</styled-x><scripts>too</scripts>"),
+            " Kept  too "
+        );
+        assert_eq!(strip_html("<style>never closed"), " ");
+        assert_eq!(strip_html("Unclosed <tag"), "Unclosed ");
+        assert_eq!(strip_html("&lt;b&gt; &amp;amp;"), "<b> &amp;");
+    }
+
+    #[test]
+    fn strip_html_handles_many_styles() {
+        let html = "<style>x</style>text ".repeat(10_000);
+        assert_eq!(strip_html(&html).matches("text").count(), 10_000);
+    }
+
+    #[test]
+    fn find_ignore_ascii_case_finds_the_first_match() {
+        assert_eq!(
+            find_ignore_ascii_case("a</STYLE></style>", "</style"),
+            Some(1)
+        );
+        assert_eq!(find_ignore_ascii_case("é</Script>", "</script"), Some(2));
+        assert_eq!(find_ignore_ascii_case("</sty", "</style"), None);
+    }
+
+    #[test]
+    fn phrases_and_negated_fields() {
+        let index = index();
+        assert_eq!(ids(&index, r#""project kickoff""#), vec!["m1", "m2"]);
+        assert_eq!(ids(&index, r#""project kickoff" -unread:true"#), vec!["m2"]);
+        assert_eq!(ids(&index, "unread:true"), vec!["m1"]);
+        assert_eq!(ids(&index, "subject:lunch AND from:alice"), vec!["m3"]);
+        assert_eq!(ids(&index, "minutes OR pizza"), vec!["m2", "m3"]);
+    }
+
+    #[test]
+    fn date_ranges() {
+        let index = index();
+        assert_eq!(
+            ids(&index, "date:[1700000000 TO 1800000000]"),
+            vec!["m1", "m2"]
+        );
+        assert_eq!(
+            ids(&index, "project AND date:[1720000000 TO *]"),
+            vec!["m2"]
+        );
+    }
+
+    #[test]
+    fn reindexing_and_removing_messages() {
+        let mut index = index();
+        index
+            .index_message(&message("m3", "Dinner", "", 1_850_000_000, false))
+            .unwrap();
+        index.remove_message("m1");
+        index.commit().unwrap();
+
+        assert!(ids(&index, "lunch").is_empty());
+        assert_eq!(ids(&index, "dinner"), vec!["m3"]);
+        assert_eq!(ids(&index, "project"), vec!["m2"]);
+    }
+
+    #[test]
+    fn invalid_queries_are_errors() {
+        let error = index().search("date:[1 TO", 10).unwrap_err();
+        assert!(error.starts_with("Invalid search query"), "{}", error);
+    }
+
+    // Rejects every object, like a store whose database is unavailable
+    struct Rejecting;
+
+    impl Store for Rejecting {
+        fn load_cursor(&self, _account_id: &str) -> Result<Option<String>, String> {
+            Ok(None)
+        }
+
+        fn save_cursor(&mut self, _account_id: &str, _cursor: &str) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn load_backfill(&self, _account_id: &str) -> Result<Option<Backfill>, String> {
+            Ok(None)
+        }
+
+        fn save_backfill(
+            &mut self,
+            _account_id: &str,
+            _progress: Option<&Backfill>,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn upsert(&mut self, _object: DeltaObject) -> Result<(), String> {
+            Err("Store unavailable".to_string())
+        }
+
+        fn delete(&mut self, _object_type: &ObjectType, _id: &str) -> Result<(), String> {
+            Err("Store unavailable".to_string())
+        }
+    }
+
+    fn upsert<S: Store>(store: &mut SearchStore<S>, id: &str, subject: &str) -> Result<(), String> {
+        let message = message(id, subject, "", 1_700_000_000, false);
+        store.upsert(DeltaObject::Message(Box::new(message)))
+    }
+
+    #[test]
+    fn search_store_commits_the_index_on_flush() {
+        let index = SearchIndex::create_in_ram().unwrap();
+        let mut store = SearchStore::new(MemoryStore::new(), index);
+        for (id, cursor) in [("m1", "c1"), ("m2", "c2")] {
+            upsert(&mut store, id, "Invoice").unwrap();
+            store.save_cursor("a1", cursor).unwrap();
+        }
+        let progress = Backfill {
+            cursor: "c0".to_string(),
+            collection: "messages".to_string(),
+            offset: 2,
+        };
+        store.save_backfill("a1", Some(&progress)).unwrap();
+
+        // The inner store only gets the cursor and progress once the index is committed
+        assert!(ids(store.index(), "invoice").is_empty());
+        assert_eq!(store.inner().load_cursor("a1").unwrap(), None);
+        assert_eq!(store.inner().load_backfill("a1").unwrap(), None);
+        assert_eq!(store.load_cursor("a1").unwrap().as_deref(), Some("c2"));
+        assert_eq!(store.load_backfill("a1").unwrap(), Some(progress.clone()));
+
+        store.flush().unwrap();
+        assert_eq!(ids(store.index(), "invoice"), vec!["m1", "m2"]);
+        assert_eq!(
+            store.inner().load_cursor("a1").unwrap().as_deref(),
+            Some("c2")
+        );
+        assert_eq!(store.inner().load_backfill("a1").unwrap(), Some(progress));
+
+        // Without pending changes, cursors go straight to the inner store
+        store.save_cursor("a1", "c3").unwrap();
+        assert_eq!(
+            store.inner().load_cursor("a1").unwrap().as_deref(),
+            Some("c3")
+        );
+    }
+
+    #[test]
+    fn search_store_skips_messages_the_inner_store_rejects() {
+        let index = SearchIndex::create_in_ram().unwrap();
+        let mut store = SearchStore::new(Rejecting, index);
+
+        assert_eq!(
+            upsert(&mut store, "m1", "Invoice").unwrap_err(),
+            "Store unavailable"
+        );
+        store.flush().unwrap();
+        assert!(ids(store.index(), "invoice").is_empty());
+    }
+}
@@ -0,0 +1,921 @@
+//! SQLite storage backend for the [`sync`](crate::sync) engine.
+//!
+//! [`SqliteStore`] persists messages, threads, events, contacts, folders, labels, cursors and
+//! backfill progress in a local database, so an application can serve the inbox offline and
+//! resume syncing after a restart. Messages are indexed on their thread, date, senders and
+//! recipients, and unread state.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nylas::deltas::DeltaObject;
+//! use nylas::messages::Message;
+//! use nylas::sqlite::SqliteStore;
+//! use nylas::sync::Store;
+//! use std::collections::HashMap;
+//!
+//! let mut store = SqliteStore::open_in_memory().unwrap();
+//!
+//! let message: Message = serde_json::from_str(r#"{
+//!     "id": "m1", "object": "message", "account_id": "a1", "thread_id": "t1",
+//!     "subject": "Invoice #42", "from": [{"email": "alice@example.com", "name": "Alice"}],
+//!     "to": [{"email": "bob@example.com", "name": null}], "cc": [], "bcc": [], "reply_to": [],
+//!     "date": 1700000000, "unread": true, "starred": false, "snippet": "Please find attached",
+//!     "body": "<p>Please find attached</p>", "files": [], "events": [], "folder": null,
+//!     "labels": [], "headers": null
+//! }"#).unwrap();
+//! store.upsert(DeltaObject::Message(Box::new(message))).unwrap();
+//!
+//! let mut filter = HashMap::new();
+//! filter.insert("from", "alice@example.com");
+//! filter.insert("unread", "true");
+//! let messages = store.messages_where(&filter, None, None).unwrap();
+//! assert_eq!(messages.len(), 1);
+//! assert_eq!(messages[0].subject, "Invoice #42");
+//! ```
+
+use crate::contacts::Contact;
+use crate::deltas::{DeltaObject, ObjectType};
+use crate::messages::{filter_date, filter_flag, Event, Folder, Label, Message};
+use crate::sync::{Backfill, Store};
+use crate::threads::Thread;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cursors (
+    account_id TEXT PRIMARY KEY,
+    cursor TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS backfills (
+    account_id TEXT PRIMARY KEY,
+    cursor TEXT NOT NULL,
+    collection TEXT NOT NULL,
+    stored INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS messages (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    snippet TEXT NOT NULL,
+    body TEXT NOT NULL,
+    date INTEGER NOT NULL,
+    unread INTEGER NOT NULL,
+    starred INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS messages_thread_id ON messages (thread_id);
+CREATE INDEX IF NOT EXISTS messages_date ON messages (date);
+CREATE INDEX IF NOT EXISTS messages_unread ON messages (unread, date);
+
+CREATE TABLE IF NOT EXISTS message_addresses (
+    message_id TEXT NOT NULL REFERENCES messages (id) ON DELETE CASCADE,
+    field TEXT NOT NULL,
+    email TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS message_addresses_email ON message_addresses (email, field);
+CREATE INDEX IF NOT EXISTS message_addresses_message_id ON message_addresses (message_id);
+
+CREATE TABLE IF NOT EXISTS message_labels (
+    message_id TEXT NOT NULL REFERENCES messages (id) ON DELETE CASCADE,
+    name TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS message_labels_name ON message_labels (name);
+CREATE INDEX IF NOT EXISTS message_labels_message_id ON message_labels (message_id);
+
+CREATE TABLE IF NOT EXISTS threads (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    last_message_timestamp INTEGER,
+    unread INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS threads_last_message_timestamp ON threads (last_message_timestamp);
+
+CREATE TABLE IF NOT EXISTS events (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    calendar_id TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS events_calendar_id ON events (calendar_id);
+
+CREATE TABLE IF NOT EXISTS contacts (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS containers (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+";
+
+/// A [`Store`] persisting the synced objects in a SQLite database.
+pub struct SqliteStore {
+    connection: Connection,
+}
+
+impl SqliteStore {
+    /// Open (or create) the database at `path` and create the missing tables.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let connection = Connection::open(path).map_err(|e| format!("SQLite Error: {}", e))?;
+        Self::from_connection(connection)
+    }
+
+    /// Open a database living in memory, mostly useful for tests.
+    pub fn open_in_memory() -> Result<Self, String> {
+        let connection =
+            Connection::open_in_memory().map_err(|e| format!("SQLite Error: {}", e))?;
+        Self::from_connection(connection)
+    }
+
+    /// Use an existing connection and create the missing tables.
+    pub fn from_connection(connection: Connection) -> Result<Self, String> {
+        connection
+            .execute_batch("PRAGMA foreign_keys = ON;")
+            .and_then(|_| connection.execute_batch(SCHEMA))
+            .map_err(|e| format!("SQLite Error: {}", e))?;
+        Ok(SqliteStore { connection })
+    }
+
+    /// The underlying connection, to run custom queries.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Retrieve a stored message by its ID.
+    pub fn message(&self, id: &str) -> Result<Option<Message>, String> {
+        self.get("SELECT data FROM messages WHERE id = ?1", id)
+    }
+
+    /// Retrieve the stored messages matching a filter, most recent first.
+    ///
+    /// # Arguments
+    ///
+    /// - `filter`: A hashmap containing filtering criteria as key-value pairs.
+    /// - `limit`: An optional limit to specify the number of results to retrieve.
+    /// - `offset`: An optional offset to specify the starting point of results.
+    ///
+    /// # Filtering Criteria
+    ///
+    /// The criteria are the ones of `Messages::where_`:
+    ///
+    /// - `"to"`, `"from"`, `"cc"`, `"bcc"`: Messages involving a specific email address in the field.
+    /// - `"date"`: Messages with a specific Unix timestamp.
+    /// - `"unread"`, `"starred"`: Messages marked as unread or starred (true or false).
+    /// - `"snippet"`, `"subject"`, `"body"`: Messages whose field contains a specific keyword.
+    /// - `"thread_id"`: Messages belonging to a specific thread (by ID).
+    /// - `"labels"`: Messages with any of the given labels (comma-separated).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `date` isn't a Unix timestamp or if `unread` or `starred` isn't
+    /// `true` or `false`.
+    pub fn messages_where(
+        &self,
+        filter: &HashMap<&str, &str>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<Message>, String> {
+        let mut conditions: Vec<String> = Vec::new();
+        let mut values: Vec<SqlValue> = Vec::new();
+
+        for field in ["to", "from", "cc", "bcc"] {
+            if let Some(email) = filter.get(field) {
+                conditions.push(
+                    "EXISTS (SELECT 1 FROM message_addresses a \
+                     WHERE a.message_id = messages.id AND a.field = ? AND a.email = ?)"
+                        .to_string(),
+                );
+                values.push(SqlValue::Text(field.to_string()));
+                values.push(SqlValue::Text(email.to_string()));
+            }
+        }
+
+        if let Some(date) = filter_date(filter)? {
+            conditions.push("date = ?".to_string());
+            values.push(SqlValue::Integer(date));
+        }
+
+        for field in ["unread", "starred"] {
+            if let Some(flag) = filter_flag(filter, field)? {
+                conditions.push(format!("{} = ?", field));
+                values.push(SqlValue::Integer(flag as i64));
+            }
+        }
+
+        for field in ["snippet", "subject", "body"] {
+            if let Some(keyword) = filter.get(field) {
+                conditions.push(format!("instr({}, ?) > 0", field));
+                values.push(SqlValue::Text(keyword.to_string()));
+            }
+        }
+
+        if let Some(thread_id) = filter.get("thread_id") {
+            conditions.push("thread_id = ?".to_string());
+            values.push(SqlValue::Text(thread_id.to_string()));
+        }
+
+        if let Some(labels) = filter.get("labels") {
+            let labels: Vec<&str> = labels.split(',').collect();
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM message_labels l \
+                 WHERE l.message_id = messages.id AND l.name IN ({}))",
+                vec!["?"; labels.len()].join(", ")
+            ));
+            values.extend(labels.iter().map(|label| SqlValue::Text(label.to_string())));
+        }
+
+        let mut sql = "SELECT data FROM messages".to_string();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY date DESC, id ASC LIMIT ? OFFSET ?");
+        values.push(SqlValue::Integer(limit.unwrap_or(-1)));
+        values.push(SqlValue::Integer(offset.unwrap_or(0)));
+
+        self.query(&sql, params_from_iter(values))
+    }
+
+    /// Retrieve the stored messages of a thread, oldest first.
+    pub fn thread_messages(&self, thread_id: &str) -> Result<Vec<Message>, String> {
+        self.query(
+            "SELECT data FROM messages WHERE thread_id = ?1 ORDER BY date ASC",
+            params![thread_id],
+        )
+    }
+
+    /// Retrieve a stored thread by its ID.
+    pub fn thread(&self, id: &str) -> Result<Option<Thread>, String> {
+        self.get("SELECT data FROM threads WHERE id = ?1", id)
+    }
+
+    /// Retrieve the stored threads, most recently active first.
+    pub fn threads(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Thread>, String> {
+        self.query(
+            "SELECT data FROM threads ORDER BY last_message_timestamp DESC LIMIT ?1 OFFSET ?2",
+            params![limit.unwrap_or(-1), offset.unwrap_or(0)],
+        )
+    }
+
+    /// Retrieve a stored event by its ID.
+    pub fn event(&self, id: &str) -> Result<Option<Event>, String> {
+        self.get("SELECT data FROM events WHERE id = ?1", id)
+    }
+
+    /// Retrieve the stored events of a calendar.
+    pub fn calendar_events(&self, calendar_id: &str) -> Result<Vec<Event>, String> {
+        self.query(
+            "SELECT data FROM events WHERE calendar_id = ?1",
+            params![calendar_id],
+        )
+    }
+
+    /// Retrieve a stored contact by its ID.
+    pub fn contact(&self, id: &str) -> Result<Option<Contact>, String> {
+        self.get("SELECT data FROM contacts WHERE id = ?1", id)
+    }
+
+    /// Retrieve every stored contact.
+    pub fn contacts(&self) -> Result<Vec<Contact>, String> {
+        self.query("SELECT data FROM contacts", [])
+    }
+
+    /// Retrieve every stored folder.
+    pub fn folders(&self) -> Result<Vec<Folder>, String> {
+        self.query("SELECT data FROM containers WHERE kind = 'folder'", [])
+    }
+
+    /// Retrieve every stored label.
+    pub fn labels(&self) -> Result<Vec<Label>, String> {
+        self.query("SELECT data FROM containers WHERE kind = 'label'", [])
+    }
+
+    fn get<T: DeserializeOwned>(&self, sql: &str, id: &str) -> Result<Option<T>, String> {
+        let data: Option<String> = self
+            .connection
+            .query_row(sql, params![id], |row| row.get(0))
+            .optional()
+            .map_err(|e| format!("SQLite Error: {}", e))?;
+        data.map(|data| {
+            serde_json::from_str(&data).map_err(|e| format!("JSON Parsing Error: {:?}", e))
+        })
+        .transpose()
+    }
+
+    fn query<T: DeserializeOwned, P: rusqlite::Params>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> Result<Vec<T>, String> {
+        let mut statement = self
+            .connection
+            .prepare(sql)
+            .map_err(|e| format!("SQLite Error: {}", e))?;
+        let rows = statement
+            .query_map(params, |row| row.get::<_, String>(0))
+            .map_err(|e| format!("SQLite Error: {}", e))?;
+
+        let mut objects = Vec::new();
+        for data in rows {
+            let data = data.map_err(|e| format!("SQLite Error: {}", e))?;
+            objects.push(
+                serde_json::from_str(&data).map_err(|e| format!("JSON Parsing Error: {:?}", e))?,
+            );
+        }
+        Ok(objects)
+    }
+
+    fn upsert_message(&mut self, message: &Message) -> Result<(), rusqlite::Error> {
+        let data = serde_json::to_string(message)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT OR REPLACE INTO messages \
+             (id, account_id, thread_id, subject, snippet, body, date, unread, starred, data) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                message.id,
+                message.account_id,
+                message.thread_id,
+                message.subject,
+                message.snippet,
+                message.body,
+                message.date,
+                message.unread,
+                message.starred,
+                data,
+            ],
+        )?;
+
+        transaction.execute(
+            "DELETE FROM message_addresses WHERE message_id = ?1",
+            params![message.id],
+        )?;
+        for (field, addresses) in [
+            ("to", &message.to),
+            ("from", &message.from),
+            ("cc", &message.cc),
+            ("bcc", &message.bcc),
+        ] {
+            for address in addresses {
+                transaction.execute(
+                    "INSERT INTO message_addresses (message_id, field, email) VALUES (?1, ?2, ?3)",
+                    params![message.id, field, address.email],
+                )?;
+            }
+        }
+
+        transaction.execute(
+            "DELETE FROM message_labels WHERE message_id = ?1",
+            params![message.id],
+        )?;
+        for label in &message.labels {
+            transaction.execute(
+                "INSERT INTO message_labels (message_id, name) VALUES (?1, ?2)",
+                params![message.id, label.name],
+            )?;
+        }
+
+        transaction.commit()
+    }
+
+    fn upsert_row(&mut self, sql: &str, values: Vec<SqlValue>) -> Result<(), rusqlite::Error> {
+        self.connection
+            .execute(sql, params_from_iter(values))
+            .map(|_| ())
+    }
+}
+
+impl Store for SqliteStore {
+    fn load_cursor(&self, account_id: &str) -> Result<Option<String>, String> {
+        self.connection
+            .query_row(
+                "SELECT cursor FROM cursors WHERE account_id = ?1",
+                params![account_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("SQLite Error: {}", e))
+    }
+
+    fn save_cursor(&mut self, account_id: &str, cursor: &str) -> Result<(), String> {
+        self.connection
+            .execute(
+                "INSERT OR REPLACE INTO cursors (account_id, cursor) VALUES (?1, ?2)",
+                params![account_id, cursor],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("SQLite Error: {}", e))
+    }
+
+    fn load_backfill(&self, account_id: &str) -> Result<Option<Backfill>, String> {
+        self.connection
+            .query_row(
+                "SELECT cursor, collection, stored FROM backfills WHERE account_id = ?1",
+                params![account_id],
+                |row| {
+                    Ok(Backfill {
+                        cursor: row.get(0)?,
+                        collection: row.get(1)?,
+                        offset: row.get(2)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| format!("SQLite Error: {}", e))
+    }
+
+    fn save_backfill(
+        &mut self,
+        account_id: &str,
+        progress: Option<&Backfill>,
+    ) -> Result<(), String> {
+        let result = match progress {
+            Some(progress) => self.connection.execute(
+                "INSERT OR REPLACE INTO backfills (account_id, cursor, collection, stored) \
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    account_id,
+                    progress.cursor,
+                    progress.collection,
+                    progress.offset
+                ],
+            ),
+            None => self.connection.execute(
+                "DELETE FROM backfills WHERE account_id = ?1",
+                params![account_id],
+            ),
+        };
+        result
+            .map(|_| ())
+            .map_err(|e| format!("SQLite Error: {}", e))
+    }
+
+    fn upsert(&mut self, object: DeltaObject) -> Result<(), String> {
+        let json = |value: Result<String, serde_json::Error>| {
+            value
+                .map(SqlValue::Text)
+                .map_err(|e| format!("JSON Serialization Error: {:?}", e))
+        };
+
+        let result = match object {
+            DeltaObject::Message(message) => self.upsert_message(&message),
+            DeltaObject::Thread(thread) => {
+                let data = json(serde_json::to_string(&thread))?;
+                self.upsert_row(
+                    "INSERT OR REPLACE INTO threads \
+                     (id, account_id, last_message_timestamp, unread, data) \
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    vec![
+                        SqlValue::Text(thread.id.clone()),
+                        SqlValue::Text(thread.account_id.clone()),
+                        thread
+                            .last_message_timestamp
+                            .map_or(SqlValue::Null, SqlValue::Integer),
+                        SqlValue::Integer(thread.unread as i64),
+                        data,
+                    ],
+                )
+            }
+            DeltaObject::Event(event) => {
+                let data = json(serde_json::to_string(&event))?;
+                self.upsert_row(
+                    "INSERT OR REPLACE INTO events (id, account_id, calendar_id, data) \
+                     VALUES (?1, ?2, ?3, ?4)",
+                    vec![
+                        SqlValue::Text(event.id.clone()),
+                        SqlValue::Text(event.account_id.clone()),
+                        SqlValue::Text(event.calendar_id.clone()),
+                        data,
+                    ],
+                )
+            }
+            DeltaObject::Contact(contact) => {
+                let data = json(serde_json::to_string(&contact))?;
+                self.upsert_row(
+                    "INSERT OR REPLACE INTO contacts (id, account_id, data) VALUES (?1, ?2, ?3)",
+                    vec![
+                        SqlValue::Text(contact.id.clone()),
+                        SqlValue::Text(contact.account_id.clone()),
+                        data,
+                    ],
+                )
+            }
+            DeltaObject::Folder(folder) => {
+                let data = json(serde_json::to_string(&folder))?;
+                self.upsert_row(
+                    "INSERT OR REPLACE INTO containers (id, kind, data) VALUES (?1, 'folder', ?2)",
+                    vec![SqlValue::Text(folder.id.clone()), data],
+                )
+            }
+            DeltaObject::Label(label) => {
+                let data = json(serde_json::to_string(&label))?;
+                self.upsert_row(
+                    "INSERT OR REPLACE INTO containers (id, kind, data) VALUES (?1, 'label', ?2)",
+                    vec![SqlValue::Text(label.id.clone()), data],
+                )
+            }
+            // Other objects are not replicated
+            _ => Ok(()),
+        };
+
+        result.map_err(|e| format!("SQLite Error: {}", e))
+    }
+
+    fn delete(&mut self, object_type: &ObjectType, id: &str) -> Result<(), String> {
+        let sql = match object_type {
+            ObjectType::Message => "DELETE FROM messages WHERE id = ?1",
+            ObjectType::Thread => "DELETE FROM threads WHERE id = ?1",
+            ObjectType::Event => "DELETE FROM events WHERE id = ?1",
+            ObjectType::Contact => "DELETE FROM contacts WHERE id = ?1",
+            ObjectType::Folder | ObjectType::Label => "DELETE FROM containers WHERE id = ?1",
+            _ => return Ok(()),
+        };
+
+        self.connection
+            .execute(sql, params![id])
+            .map(|_| ())
+            .map_err(|e| format!("SQLite Error: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::MemoryStore;
+    use serde::Serialize;
+    use serde_json::{json, Value};
+
+    fn message(id: &str, date: i64, extra: Value) -> Message {
+        let mut message = json!({
+            "id": id, "object": "message", "account_id": "a1", "thread_id": "t1",
+            "subject": "Hello", "from": [{"email": "alice@example.com", "name": "Alice"}],
+            "to": [{"email": "bob@example.com", "name": null}], "cc": [], "bcc": [],
+            "reply_to": [], "date": date, "unread": false, "starred": false, "snippet": "",
+            "body": "", "files": [], "events": [], "folder": null, "labels": [], "headers": null
+        });
+        for (key, value) in extra.as_object().unwrap() {
+            message[key] = value.clone();
+        }
+        serde_json::from_value(message).unwrap()
+    }
+
+    fn object<T: DeserializeOwned>(value: Value) -> T {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn thread(id: &str) -> Thread {
+        object(json!({
+            "id": id, "object": "thread", "account_id": "a1", "subject": "Hello",
+            "snippet": null, "unread": true, "starred": false, "has_attachments": false,
+            "version": 1, "first_message_timestamp": 1700000000,
+            "last_message_timestamp": 1700000100, "last_message_received_timestamp": null,
+            "last_message_sent_timestamp": null
+        }))
+    }
+
+    fn event(id: &str) -> Event {
+        object(json!({
+            "id": id, "object": "event", "account_id": "a1", "calendar_id": "c1", "busy": true,
+            "participants": [], "read_only": false, "reminders": null, "visibility": null,
+            "when": {"time": 1700000000}
+        }))
+    }
+
+    fn contact(id: &str) -> Contact {
+        object(json!({
+            "id": id, "object": "contact", "account_id": "a1", "given_name": "Alice",
+            "middle_name": null, "surname": null, "suffix": null, "nickname": null,
+            "company_name": null, "job_title": null, "notes": null, "birthday": null,
+            "picture_url": null, "source": null
+        }))
+    }
+
+    fn folder(id: &str) -> Folder {
+        object(json!({"id": id, "display_name": "Inbox", "name": "inbox"}))
+    }
+
+    fn label(id: &str) -> Label {
+        object(json!({"id": id, "display_name": "Work", "name": "work"}))
+    }
+
+    fn same<T: Serialize>(actual: T, expected: T) {
+        assert_eq!(
+            serde_json::to_value(actual).unwrap(),
+            serde_json::to_value(expected).unwrap()
+        );
+    }
+
+    fn ids(messages: Vec<Message>) -> Vec<String> {
+        messages.into_iter().map(|message| message.id).collect()
+    }
+
+    #[test]
+    fn objects_are_saved_and_loaded() {
+        let mut store = SqliteStore::open_in_memory().unwrap();
+        let message = message("m1", 1700000000, json!({"labels": [label("l1")]}));
+        store
+            .upsert(DeltaObject::Message(Box::new(message.clone())))
+            .unwrap();
+        store
+            .upsert(DeltaObject::Thread(Box::new(thread("t1"))))
+            .unwrap();
+        store
+            .upsert(DeltaObject::Event(Box::new(event("e1"))))
+            .unwrap();
+        store
+            .upsert(DeltaObject::Contact(Box::new(contact("p1"))))
+            .unwrap();
+        store.upsert(DeltaObject::Folder(folder("f1"))).unwrap();
+        store.upsert(DeltaObject::Label(label("l1"))).unwrap();
+
+        same(store.message("m1").unwrap(), Some(message.clone()));
+        same(store.thread_messages("t1").unwrap(), vec![message]);
+        same(store.thread("t1").unwrap(), Some(thread("t1")));
+        same(store.threads(None, None).unwrap(), vec![thread("t1")]);
+        same(store.event("e1").unwrap(), Some(event("e1")));
+        same(store.calendar_events("c1").unwrap(), vec![event("e1")]);
+        same(store.contact("p1").unwrap(), Some(contact("p1")));
+        same(store.contacts().unwrap(), vec![contact("p1")]);
+        same(store.folders().unwrap(), vec![folder("f1")]);
+        same(store.labels().unwrap(), vec![label("l1")]);
+        assert!(store.message("m2").unwrap().is_none());
+    }
+
+    #[test]
+    fn upserts_replace_the_stored_object_and_its_index() {
+        let mut store = SqliteStore::open_in_memory().unwrap();
+        let first = message("m1", 1700000000, json!({"subject": "Draft"}));
+        let second = message(
+            "m1",
+            1700000000,
+            json!({"subject": "Final", "to": [{"email": "carol@example.com", "name": null}]}),
+        );
+        store.upsert(DeltaObject::Message(Box::new(first))).unwrap();
+        store
+            .upsert(DeltaObject::Message(Box::new(second.clone())))
+            .unwrap();
+
+        same(store.message("m1").unwrap(), Some(second));
+        let to_bob = HashMap::from([("to", "bob@example.com")]);
+        assert!(store
+            .messages_where(&to_bob, None, None)
+            .unwrap()
+            .is_empty());
+        let to_carol = HashMap::from([("to", "carol@example.com")]);
+        assert_eq!(
+            ids(store.messages_where(&to_carol, None, None).unwrap()),
+            vec!["m1"]
+        );
+    }
+
+    #[test]
+    fn objects_are_deleted() {
+        let mut store = SqliteStore::open_in_memory().unwrap();
+        let message = message("m1", 1700000000, json!({"labels": [label("l1")]}));
+        store
+            .upsert(DeltaObject::Message(Box::new(message)))
+            .unwrap();
+        store
+            .upsert(DeltaObject::Thread(Box::new(thread("t1"))))
+            .unwrap();
+        store
+            .upsert(DeltaObject::Event(Box::new(event("e1"))))
+            .unwrap();
+        store
+            .upsert(DeltaObject::Contact(Box::new(contact("p1"))))
+            .unwrap();
+        store.upsert(DeltaObject::Folder(folder("f1"))).unwrap();
+        store.upsert(DeltaObject::Label(label("l1"))).unwrap();
+
+        for (object_type, id) in [
+            (ObjectType::Message, "m1"),
+            (ObjectType::Thread, "t1"),
+            (ObjectType::Event, "e1"),
+            (ObjectType::Contact, "p1"),
+            (ObjectType::Folder, "f1"),
+            (ObjectType::Label, "l1"),
+        ] {
+            store.delete(&object_type, id).unwrap();
+            // Deleting an object which isn't stored is not an error
+            store.delete(&object_type, id).unwrap();
+        }
+
+        assert!(store.message("m1").unwrap().is_none());
+        assert!(store.thread("t1").unwrap().is_none());
+        assert!(store.event("e1").unwrap().is_none());
+        assert!(store.contact("p1").unwrap().is_none());
+        assert!(store.folders().unwrap().is_empty());
+        assert!(store.labels().unwrap().is_empty());
+
+        // The index rows of the message are deleted along with it
+        let count = |table: &str| -> i64 {
+            store
+                .connection()
+                .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                    row.get(0)
+                })
+                .unwrap()
+        };
+        assert_eq!(count("message_addresses"), 0);
+        assert_eq!(count("message_labels"), 0);
+    }
+
+    #[test]
+    fn cursors_are_persisted() {
+        let mut store = SqliteStore::open_in_memory().unwrap();
+        assert_eq!(store.load_cursor("a1").unwrap(), None);
+        store.save_cursor("a1", "c1").unwrap();
+        store.save_cursor("a1", "c2").unwrap();
+        store.save_cursor("a2", "c9").unwrap();
+        assert_eq!(store.load_cursor("a1").unwrap().as_deref(), Some("c2"));
+        assert_eq!(store.load_cursor("a2").unwrap().as_deref(), Some("c9"));
+
+        // The backfill progress is kept apart from the cursor
+        let progress = Backfill {
+            cursor: "c2".to_string(),
+            collection: "messages".to_string(),
+            offset: 100,
+        };
+        store.save_backfill("a1", Some(&progress)).unwrap();
+        assert_eq!(store.load_backfill("a1").unwrap(), Some(progress));
+        assert_eq!(store.load_cursor("a1").unwrap().as_deref(), Some("c2"));
+        store.save_backfill("a1", None).unwrap();
+        assert_eq!(store.load_backfill("a1").unwrap(), None);
+        let count: i64 = store
+            .connection
+            .query_row("SELECT COUNT(*) FROM backfills", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    // Three messages with distinct values for every criterion
+    fn stores() -> (SqliteStore, MemoryStore) {
+        let messages = [
+            message(
+                "m1",
+                1700000000,
+                json!({"unread": true, "subject": "Invoice #42", "snippet": "Please pay",
+                       "body": "<p>Please pay</p>", "labels": [label("l1")]}),
+            ),
+            message(
+                "m2",
+                1700000100,
+                json!({"thread_id": "t2", "starred": true, "subject": "Lunch",
+                       "from": [{"email": "carol@example.com", "name": null}],
+                       "cc": [{"email": "dave@example.com", "name": null}]}),
+            ),
+            message(
+                "m3",
+                1700000200,
+                json!({"bcc": [{"email": "erin@example.com", "name": null}],
+                       "labels": [{"id": "l2", "display_name": "Home", "name": "home"}]}),
+            ),
+        ];
+
+        let mut sqlite = SqliteStore::open_in_memory().unwrap();
+        let mut memory = MemoryStore::new();
+        for message in messages {
+            sqlite
+                .upsert(DeltaObject::Message(Box::new(message.clone())))
+                .unwrap();
+            memory
+                .upsert(DeltaObject::Message(Box::new(message)))
+                .unwrap();
+        }
+        (sqlite, memory)
+    }
+
+    #[test]
+    fn each_filter_matches_the_same_messages_in_both_stores() {
+        let (sqlite, memory) = stores();
+        for (filter, expected) in [
+            (vec![], vec!["m3", "m2", "m1"]),
+            (vec![("to", "bob@example.com")], vec!["m3", "m2", "m1"]),
+            (vec![("from", "carol@example.com")], vec!["m2"]),
+            (vec![("cc", "dave@example.com")], vec!["m2"]),
+            (vec![("bcc", "erin@example.com")], vec!["m3"]),
+            (vec![("date", "1700000100")], vec!["m2"]),
+            (vec![("unread", "true")], vec!["m1"]),
+            (vec![("unread", "false")], vec!["m3", "m2"]),
+            (vec![("starred", "true")], vec!["m2"]),
+            (vec![("snippet", "pay")], vec!["m1"]),
+            (vec![("subject", "Lunch")], vec!["m2"]),
+            (vec![("body", "<p>")], vec!["m1"]),
+            (vec![("thread_id", "t2")], vec!["m2"]),
+            (vec![("labels", "work,home")], vec!["m3", "m1"]),
+            (vec![("labels", "work"), ("unread", "false")], vec![]),
+        ] {
+            let filter: HashMap<&str, &str> = filter.into_iter().collect();
+            assert_eq!(
+                ids(sqlite.messages_where(&filter, None, None).unwrap()),
+                expected,
+                "{:?}",
+                filter
+            );
+            assert_eq!(
+                ids(memory.messages_where(&filter, None, None).unwrap()),
+                expected,
+                "{:?}",
+                filter
+            );
+        }
+
+        let filter = HashMap::new();
+        for (limit, offset, expected) in [
+            (Some(2), None, vec!["m3", "m2"]),
+            (Some(2), Some(2), vec!["m1"]),
+            (None, Some(1), vec!["m2", "m1"]),
+            (Some(-1), Some(1), vec!["m2", "m1"]),
+        ] {
+            assert_eq!(
+                ids(sqlite.messages_where(&filter, limit, offset).unwrap()),
+                expected
+            );
+            assert_eq!(
+                ids(memory.messages_where(&filter, limit, offset).unwrap()),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn messages_with_the_same_date_are_paged_by_id_in_both_stores() {
+        let mut sqlite = SqliteStore::open_in_memory().unwrap();
+        let mut memory = MemoryStore::new();
+        for id in ["m3", "m1", "m2"] {
+            let message = message(id, 1700000000, json!({}));
+            sqlite
+                .upsert(DeltaObject::Message(Box::new(message.clone())))
+                .unwrap();
+            memory
+                .upsert(DeltaObject::Message(Box::new(message)))
+                .unwrap();
+        }
+
+        let filter = HashMap::new();
+        for (offset, expected) in [(0, "m1"), (1, "m2"), (2, "m3")] {
+            assert_eq!(
+                ids(sqlite
+                    .messages_where(&filter, Some(1), Some(offset))
+                    .unwrap()),
+                vec![expected]
+            );
+            assert_eq!(
+                ids(memory
+                    .messages_where(&filter, Some(1), Some(offset))
+                    .unwrap()),
+                vec![expected]
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_filters_are_rejected_by_both_stores() {
+        let (sqlite, memory) = stores();
+        for (filter, error) in [
+            (
+                ("date", "yesterday"),
+                "Invalid filter `date`: expected a Unix timestamp, got `yesterday`.",
+            ),
+            (
+                ("unread", "yes"),
+                "Invalid filter `unread`: expected true or false, got `yes`.",
+            ),
+            (
+                ("starred", "1"),
+                "Invalid filter `starred`: expected true or false, got `1`.",
+            ),
+        ] {
+            let filter = HashMap::from([filter]);
+            assert_eq!(
+                sqlite.messages_where(&filter, None, None).unwrap_err(),
+                error
+            );
+            assert_eq!(
+                memory.messages_where(&filter, None, None).unwrap_err(),
+                error
+            );
+        }
+
+        // Empty stores reject them as well
+        let filter = HashMap::from([("unread", "yes")]);
+        let empty = SqliteStore::open_in_memory().unwrap();
+        assert!(empty.messages_where(&filter, None, None).is_err());
+        assert!(MemoryStore::new()
+            .messages_where(&filter, None, None)
+            .is_err());
+    }
+}
@@ -0,0 +1,692 @@
+//! Local incremental replica of an account.
+//!
+//! A [`SyncEngine`] keeps a [`Store`] up to date with the messages, threads, folders or labels
+//! and contacts of an account. The first sync backfills the store, then every following sync
+//! only applies the deltas that happened since the cursor saved in the store, so the replica
+//! resumes where it left off after a restart. The backfill itself stores every page as soon as
+//! it is received and saves its progress, so an interrupted backfill resumes as well.
+
+use crate::accounts::OrganizationUnit;
+use crate::client::Nylas;
+use crate::contacts::Contact;
+use crate::deltas::{Delta, DeltaObject, DeltaOptions, ObjectType};
+use crate::messages::{self, Folder, Label, Message};
+use crate::threads::Thread;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Storage backend of a [`SyncEngine`].
+///
+/// Implementations must make `upsert` and `delete` idempotent: after a crash, the deltas
+/// following the last saved cursor are applied again.
+pub trait Store {
+    /// Load the cursor saved for the account, or `None` if the account was never synced.
+    fn load_cursor(&self, account_id: &str) -> Result<Option<String>, String>;
+
+    /// Save the cursor of the last change applied for the account.
+    fn save_cursor(&mut self, account_id: &str, cursor: &str) -> Result<(), String>;
+
+    /// Insert an object, or replace it if it is already stored.
+    fn upsert(&mut self, object: DeltaObject) -> Result<(), String>;
+
+    /// Remove an object. Removing an object which isn't stored is not an error.
+    fn delete(&mut self, object_type: &ObjectType, id: &str) -> Result<(), String>;
+
+    /// Load the progress of an unfinished backfill of the account.
+    fn load_backfill(&self, account_id: &str) -> Result<Option<Backfill>, String>;
+
+    /// Save the progress of the backfill of the account, or clear it with `None` once the
+    /// backfill completed.
+    fn save_backfill(
+        &mut self,
+        account_id: &str,
+        progress: Option<&Backfill>,
+    ) -> Result<(), String>;
+
+    /// Persist the changes written since the last flush.
+    ///
+    /// The engine flushes the store once per page of deltas or backfilled objects, and at the
+    /// end of every run. Does nothing by default.
+    fn flush(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// The progress of a backfill, saved after every page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Backfill {
+    /// The cursor fetched when the backfill started, saved once it completes.
+    pub cursor: String,
+    /// The collection being listed, e.g. `messages`.
+    pub collection: String,
+    /// The number of objects of the collection already stored.
+    pub offset: usize,
+}
+
+/// An in-memory [`Store`], mostly useful for tests and short-lived processes.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    pub cursors: HashMap<String, String>,
+    pub backfills: HashMap<String, Backfill>,
+    pub messages: HashMap<String, Message>,
+    pub threads: HashMap<String, Thread>,
+    pub folders: HashMap<String, Folder>,
+    pub labels: HashMap<String, Label>,
+    pub contacts: HashMap<String, Contact>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+
+    /// Retrieve the stored messages matching a filter, most recent first.
+    ///
+    /// The criteria are the ones of `Messages::where_`, and the results the same as the ones of
+    /// `SqliteStore::messages_where`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `date` isn't a Unix timestamp or if `unread` or `starred` isn't
+    /// `true` or `false`.
+    pub fn messages_where(
+        &self,
+        filter: &HashMap<&str, &str>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<Message>, String> {
+        messages::check_filter(filter)?;
+
+        // Like SQLite, a negative limit means no limit and a negative offset none
+        let limit = limit.map_or(usize::MAX, |limit| {
+            usize::try_from(limit).unwrap_or(usize::MAX)
+        });
+        let offset = offset.map_or(0, |offset| usize::try_from(offset).unwrap_or(0));
+
+        let mut matching: Vec<&Message> = self
+            .messages
+            .values()
+            .filter(|message| message.matches_filter(filter))
+            .collect();
+        matching.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.id.cmp(&b.id)));
+
+        Ok(matching
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+}
+
+impl Store for MemoryStore {
+    fn load_cursor(&self, account_id: &str) -> Result<Option<String>, String> {
+        Ok(self.cursors.get(account_id).cloned())
+    }
+
+    fn save_cursor(&mut self, account_id: &str, cursor: &str) -> Result<(), String> {
+        self.cursors
+            .insert(account_id.to_string(), cursor.to_string());
+        Ok(())
+    }
+
+    fn load_backfill(&self, account_id: &str) -> Result<Option<Backfill>, String> {
+        Ok(self.backfills.get(account_id).cloned())
+    }
+
+    fn save_backfill(
+        &mut self,
+        account_id: &str,
+        progress: Option<&Backfill>,
+    ) -> Result<(), String> {
+        match progress {
+            Some(progress) => {
+                self.backfills
+                    .insert(account_id.to_string(), progress.clone());
+            }
+            None => {
+                self.backfills.remove(account_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn upsert(&mut self, object: DeltaObject) -> Result<(), String> {
+        match object {
+            DeltaObject::Message(message) => {
+                self.messages.insert(message.id.clone(), *message);
+            }
+            DeltaObject::Thread(thread) => {
+                self.threads.insert(thread.id.clone(), *thread);
+            }
+            DeltaObject::Folder(folder) => {
+                self.folders.insert(folder.id.clone(), folder);
+            }
+            DeltaObject::Label(label) => {
+                self.labels.insert(label.id.clone(), label);
+            }
+            DeltaObject::Contact(contact) => {
+                self.contacts.insert(contact.id.clone(), *contact);
+            }
+            // Other objects are not replicated
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, object_type: &ObjectType, id: &str) -> Result<(), String> {
+        match object_type {
+            ObjectType::Message => {
+                self.messages.remove(id);
+            }
+            ObjectType::Thread => {
+                self.threads.remove(id);
+            }
+            ObjectType::Folder => {
+                self.folders.remove(id);
+            }
+            ObjectType::Label => {
+                self.labels.remove(id);
+            }
+            ObjectType::Contact => {
+                self.contacts.remove(id);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Summary of a sync run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Whether the store was backfilled because no cursor was saved for the account.
+    pub backfilled: bool,
+    /// The number of objects stored by the backfill.
+    pub backfilled_objects: usize,
+    pub created: usize,
+    pub modified: usize,
+    pub deleted: usize,
+    /// Deltas, see [`Delta::Unknown`], and backfilled objects which couldn't be read.
+    pub skipped: usize,
+    /// The cursor saved at the end of the run.
+    pub cursor: String,
+}
+
+/// Keeps a [`Store`] in sync with the account of a `Nylas` instance.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use nylas::client::Nylas;
+/// use nylas::sync::{MemoryStore, SyncEngine};
+/// #[tokio::main]
+/// async fn main() {
+///     let access_token = "YOUR_ACCESS_TOKEN";
+///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", Some(access_token))
+///         .await
+///         .unwrap();
+///
+///     let mut engine = SyncEngine::new(MemoryStore::new());
+///
+///     // Backfill the store, then keep it up to date
+///     engine.sync(&nylas).await.unwrap();
+///     loop {
+///         let report = engine.poll(&nylas, 120).await.unwrap();
+///         println!("{} messages stored", engine.store().messages.len());
+///     }
+/// }
+/// ```
+pub struct SyncEngine<S: Store> {
+    store: S,
+    options: DeltaOptions,
+    page_size: usize,
+}
+
+impl<S: Store> SyncEngine<S> {
+    /// Create an engine replicating messages, threads, folders, labels and contacts into `store`.
+    pub fn new(store: S) -> Self {
+        SyncEngine {
+            store,
+            options: DeltaOptions {
+                include_types: vec![
+                    ObjectType::Message,
+                    ObjectType::Thread,
+                    ObjectType::Folder,
+                    ObjectType::Label,
+                    ObjectType::Contact,
+                ],
+                ..Default::default()
+            },
+            page_size: 100,
+        }
+    }
+
+    /// Replace the options used to request the deltas, e.g. to replicate fewer object types.
+    pub fn with_options(mut self, options: DeltaOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set the number of objects requested per page during the backfill.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn store_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    /// Bring the store up to date.
+    ///
+    /// If the store has no cursor for the account, the store is backfilled first: the latest
+    /// cursor is fetched before listing every object and saved once the backfill completes, so
+    /// changes made during the backfill are applied by the following deltas. Every page of
+    /// objects is stored as soon as it is received and the progress of the backfill is saved
+    /// with [`Store::save_backfill`], so a failed backfill resumes from the last stored page.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a report of the run, or an error message. The cursor of every
+    /// applied delta is saved as soon as the delta is applied, so a failed run can be resumed.
+    pub async fn sync(&mut self, nylas: &Nylas) -> Result<SyncReport, String> {
+        let account_id = Self::account_id(nylas).await?;
+        let mut report = SyncReport::default();
+
+        let mut cursor = match self.store.load_cursor(&account_id)? {
+            Some(cursor) => cursor,
+            None => {
+                let progress = match self.store.load_backfill(&account_id)? {
+                    Some(progress) => progress,
+                    None => Backfill {
+                        cursor: nylas.deltas().latest_cursor().await?,
+                        collection: String::new(),
+                        offset: 0,
+                    },
+                };
+                let cursor = progress.cursor.clone();
+                self.backfill(nylas, &account_id, progress, &mut report)
+                    .await?;
+                self.store.save_cursor(&account_id, &cursor)?;
+                self.store.save_backfill(&account_id, None)?;
+                self.store.flush()?;
+                report.backfilled = true;
+                cursor
+            }
+        };
+
+        loop {
+            let page = nylas.deltas().page(&cursor, &self.options).await?;
+            let done = page.deltas.is_empty() || page.cursor_end == page.cursor_start;
+            for delta in page.deltas {
+                self.apply(&account_id, delta, &mut report)?;
+            }
+            if page.cursor_end != cursor {
+                self.store.save_cursor(&account_id, &page.cursor_end)?;
+                cursor = page.cursor_end;
+            }
+            self.store.flush()?;
+            if done {
+                break;
+            }
+        }
+
+        report.cursor = cursor;
+        Ok(report)
+    }
+
+    /// Wait up to `timeout` seconds for changes and apply them.
+    ///
+    /// The store must have been synced with [`SyncEngine::sync`] first. Nylas answers as soon as
+    /// changes are available, so calling `poll` in a loop keeps the store up to date.
+    pub async fn poll(&mut self, nylas: &Nylas, timeout: u64) -> Result<SyncReport, String> {
+        let account_id = Self::account_id(nylas).await?;
+        let cursor = self
+            .store
+            .load_cursor(&account_id)?
+            .ok_or("The store must be synced before polling.")?;
+
+        let page = nylas
+            .deltas()
+            .longpoll(&cursor, timeout, &self.options)
+            .await?;
+
+        let mut report = SyncReport::default();
+        for delta in page.deltas {
+            self.apply(&account_id, delta, &mut report)?;
+        }
+        if page.cursor_end != cursor {
+            self.store.save_cursor(&account_id, &page.cursor_end)?;
+        }
+        self.store.flush()?;
+        report.cursor = page.cursor_end;
+        Ok(report)
+    }
+
+    /// Apply a single delta to the store and save its cursor.
+    ///
+    /// Call [`Store::flush`] once the deltas of a page are applied.
+    pub fn apply(
+        &mut self,
+        account_id: &str,
+        delta: Delta,
+        report: &mut SyncReport,
+    ) -> Result<(), String> {
+        let cursor = delta.cursor().to_string();
+        match delta {
+            Delta::Create { object, .. } => {
+                self.store.upsert(object)?;
+                report.created += 1;
+            }
+            Delta::Modify { object, .. } => {
+                self.store.upsert(object)?;
+                report.modified += 1;
+            }
+            Delta::Delete {
+                object_type, id, ..
+            } => {
+                self.store.delete(&object_type, &id)?;
+                report.deleted += 1;
+            }
+            Delta::Unknown { .. } => report.skipped += 1,
+        }
+        if cursor.is_empty() {
+            return Ok(());
+        }
+        self.store.save_cursor(account_id, &cursor)
+    }
+
+    async fn backfill(
+        &mut self,
+        nylas: &Nylas,
+        account_id: &str,
+        mut progress: Backfill,
+        report: &mut SyncReport,
+    ) -> Result<(), String> {
+        let organization_unit =
+            if self.wanted(&ObjectType::Folder) || self.wanted(&ObjectType::Label) {
+                Some(nylas.mailbox().organization_unit().await?)
+            } else {
+                None
+            };
+        let collections = self.collections(organization_unit);
+
+        // Skip the collections a previous run already stored
+        let start = collections
+            .iter()
+            .position(|(_, collection)| *collection == progress.collection)
+            .unwrap_or_else(|| {
+                progress.offset = 0;
+                0
+            });
+
+        for (object_type, collection) in &collections[start..] {
+            if progress.collection != *collection {
+                progress.collection = collection.to_string();
+                progress.offset = 0;
+            }
+
+            loop {
+                let page = self.page(nylas, collection, progress.offset).await?;
+                let count = page.len();
+                report.backfilled_objects += self.store_page(object_type, page, report)?;
+
+                progress.offset += count;
+                self.store.save_backfill(account_id, Some(&progress))?;
+                self.store.flush()?;
+                if count < self.page_size {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // The collections to list during the backfill, in order.
+    fn collections(
+        &self,
+        organization_unit: Option<OrganizationUnit>,
+    ) -> Vec<(ObjectType, &'static str)> {
+        let container = match organization_unit {
+            Some(OrganizationUnit::Folder) => Some((ObjectType::Folder, "folders")),
+            Some(OrganizationUnit::Label) => Some((ObjectType::Label, "labels")),
+            _ => None,
+        };
+        [
+            Some((ObjectType::Message, "messages")),
+            Some((ObjectType::Thread, "threads")),
+            container,
+            Some((ObjectType::Contact, "contacts")),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|(object_type, _)| self.wanted(object_type))
+        .collect()
+    }
+
+    fn wanted(&self, object_type: &ObjectType) -> bool {
+        if self.options.include_types.is_empty() {
+            !self.options.exclude_types.contains(object_type)
+        } else {
+            self.options.include_types.contains(object_type)
+        }
+    }
+
+    // Store a page of the backfill, skipping the objects which can't be read like the deltas.
+    // Returns the number of stored objects.
+    fn store_page(
+        &mut self,
+        object_type: &ObjectType,
+        page: Vec<Value>,
+        report: &mut SyncReport,
+    ) -> Result<usize, String> {
+        let mut stored = 0;
+        for object in page {
+            match DeltaObject::parse(object_type.clone(), object) {
+                Ok(object) => {
+                    self.store.upsert(object)?;
+                    stored += 1;
+                }
+                Err(_) => report.skipped += 1,
+            }
+        }
+        Ok(stored)
+    }
+
+    // Retrieve a page of a collection.
+    async fn page(&self, nylas: &Nylas, path: &str, offset: usize) -> Result<Vec<Value>, String> {
+        let url = format!(
+            "https://api.nylas.com/{}?limit={}&offset={}",
+            path, self.page_size, offset
+        );
+
+        let response = nylas
+            .send(nylas.request(Method::GET, &url).await?)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            return Err(format!("Request failed with status: {}", response.status()));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| format!("JSON Parsing Error: {:?}", e))
+    }
+
+    async fn account_id(nylas: &Nylas) -> Result<String, String> {
+        match nylas.cached_account() {
+            Some(account) => Ok(account.id),
+            None => Ok(nylas.account().await?.id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(id: &str, subject: &str) -> Value {
+        json!({
+            "id": id, "object": "message", "account_id": "a1", "thread_id": "t1",
+            "subject": subject, "from": [], "to": [], "cc": [], "bcc": [], "reply_to": [],
+            "date": 1700000000, "unread": true, "starred": false, "snippet": "", "body": "",
+            "files": [], "events": [], "folder": null, "labels": [], "headers": null
+        })
+    }
+
+    fn delta(cursor: &str, event: &str, id: &str, attributes: Option<Value>) -> Delta {
+        let mut raw = json!({"cursor": cursor, "event": event, "id": id, "object": "message"});
+        if let Some(attributes) = attributes {
+            raw["attributes"] = attributes;
+        }
+        Delta::from(raw)
+    }
+
+    #[test]
+    fn apply_creates_modifies_and_deletes() {
+        let mut engine = SyncEngine::new(MemoryStore::new());
+        let mut report = SyncReport::default();
+
+        let deltas = [
+            delta("c1", "create", "m1", Some(message("m1", "Draft"))),
+            delta("c2", "modify", "m1", Some(message("m1", "Final"))),
+            delta("c3", "create", "m2", Some(message("m2", "Other"))),
+            delta("c4", "delete", "m2", None),
+        ];
+        for delta in deltas {
+            engine.apply("a1", delta, &mut report).unwrap();
+        }
+
+        let store = engine.store();
+        assert_eq!(store.messages.len(), 1);
+        assert_eq!(store.messages["m1"].subject, "Final");
+        assert_eq!(store.cursors["a1"], "c4");
+        assert_eq!((report.created, report.modified, report.deleted), (2, 1, 1));
+    }
+
+    #[test]
+    fn apply_is_idempotent() {
+        let mut engine = SyncEngine::new(MemoryStore::new());
+        let mut report = SyncReport::default();
+
+        for _ in 0..2 {
+            engine
+                .apply(
+                    "a1",
+                    delta("c1", "create", "m1", Some(message("m1", "Hi"))),
+                    &mut report,
+                )
+                .unwrap();
+            engine
+                .apply("a1", delta("c2", "delete", "m9", None), &mut report)
+                .unwrap();
+        }
+
+        assert_eq!(engine.store().messages.len(), 1);
+        assert_eq!(engine.store().cursors["a1"], "c2");
+    }
+
+    #[test]
+    fn apply_moves_past_unreadable_deltas() {
+        let mut engine = SyncEngine::new(MemoryStore::new());
+        let mut report = SyncReport::default();
+
+        engine
+            .apply(
+                "a1",
+                delta("c1", "create", "m1", Some(json!({"id": "m1"}))),
+                &mut report,
+            )
+            .unwrap();
+        assert_eq!(report.skipped, 1);
+        assert_eq!(engine.store().cursors["a1"], "c1");
+
+        // Without a cursor, the saved cursor is kept
+        engine
+            .apply("a1", Delta::from(json!({"event": "create"})), &mut report)
+            .unwrap();
+        assert_eq!(report.skipped, 2);
+        assert_eq!(engine.store().cursors["a1"], "c1");
+        assert!(engine.store().messages.is_empty());
+    }
+
+    #[test]
+    fn backfill_progress_is_saved_and_cleared() {
+        let mut store = MemoryStore::new();
+        assert_eq!(store.load_backfill("a1").unwrap(), None);
+
+        let progress = Backfill {
+            cursor: "c0".to_string(),
+            collection: "threads".to_string(),
+            offset: 200,
+        };
+        store.save_backfill("a1", Some(&progress)).unwrap();
+        assert_eq!(store.load_backfill("a1").unwrap(), Some(progress));
+        // The progress doesn't count as a cursor of any account
+        assert!(store.cursors.is_empty());
+
+        store.save_backfill("a1", None).unwrap();
+        assert_eq!(store.load_backfill("a1").unwrap(), None);
+        assert!(store.backfills.is_empty());
+    }
+
+    #[test]
+    fn store_page_skips_unreadable_objects() {
+        let mut engine = SyncEngine::new(MemoryStore::new());
+        let mut report = SyncReport::default();
+
+        let page = vec![
+            message("m1", "One"),
+            json!({"id": "m2"}),
+            message("m3", "Three"),
+        ];
+        let stored = engine
+            .store_page(&ObjectType::Message, page, &mut report)
+            .unwrap();
+
+        assert_eq!(stored, 2);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(engine.store().messages.len(), 2);
+    }
+
+    #[test]
+    fn collections_follow_the_options_and_organization_unit() {
+        let engine = SyncEngine::new(MemoryStore::new());
+        let names = |collections: Vec<(ObjectType, &'static str)>| {
+            collections
+                .into_iter()
+                .map(|(_, name)| name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(engine.collections(Some(OrganizationUnit::Label))),
+            vec!["messages", "threads", "labels", "contacts"]
+        );
+        assert_eq!(
+            names(engine.collections(Some(OrganizationUnit::Folder))),
+            vec!["messages", "threads", "folders", "contacts"]
+        );
+
+        let engine = engine.with_options(DeltaOptions {
+            exclude_types: vec![ObjectType::Thread, ObjectType::Contact],
+            ..Default::default()
+        });
+        assert_eq!(names(engine.collections(None)), vec!["messages"]);
+    }
+}
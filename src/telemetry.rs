@@ -0,0 +1,158 @@
+//! Instrumentation of the requests sent to Nylas.
+//!
+//! Spans and events never include headers, bodies or query strings, so access tokens, client
+//! secrets and authorization codes can't leak into the logs.
+
+use reqwest::Url;
+#[cfg(feature = "tracing")]
+use reqwest::{Method, Response};
+#[cfg(feature = "tracing")]
+use std::time::Duration;
+#[cfg(feature = "tracing")]
+use tracing::{field::Empty, Span};
+
+/// The path of a URL with the IDs replaced by `{id}`, e.g. `/messages/{id}`, so requests to the
+/// same endpoint are grouped together.
+pub(crate) fn path_template(url: &Url) -> String {
+    let segments: Vec<&str> = url
+        .path()
+        .split('/')
+        .map(|segment| {
+            // Nylas IDs are long alphanumeric strings, endpoint names are short words
+            let id = segment.len() >= 16 || segment.chars().any(|c| c.is_ascii_digit());
+            if id {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect();
+    segments.join("/")
+}
+
+/// Create the span of a request.
+#[cfg(feature = "tracing")]
+pub(crate) fn request_span(method: &Method, url: &Url, account_id: Option<&str>) -> Span {
+    let span = tracing::info_span!(
+        "nylas.request",
+        http.method = %method,
+        http.path = %path_template(url),
+        http.status = Empty,
+        latency_ms = Empty,
+        retries = Empty,
+        account_id = Empty,
+    );
+    if let Some(account_id) = account_id {
+        span.record("account_id", account_id);
+    }
+    span
+}
+
+/// Record the outcome of a request on its span.
+#[cfg(feature = "tracing")]
+pub(crate) fn record_outcome(
+    span: &Span,
+    url: &Url,
+    result: &Result<Response, reqwest::Error>,
+    retries: u32,
+    latency: Duration,
+) {
+    span.record("latency_ms", latency.as_millis() as u64);
+    span.record("retries", retries);
+
+    match result {
+        Ok(response) => {
+            span.record("http.status", response.status().as_u16());
+            let status = response.status().as_u16();
+            if response.status().is_success() {
+                tracing::debug!(parent: span, status, "request succeeded");
+            } else {
+                tracing::warn!(parent: span, status, "request failed");
+            }
+        }
+        Err(error) => {
+            let message = redact_url(&error.to_string(), url);
+            tracing::warn!(parent: span, error = %message, "request error");
+        }
+    }
+}
+
+/// Replace the URL in an error message, which contains the query string, by its template.
+#[cfg(feature = "tracing")]
+fn redact_url(message: &str, url: &Url) -> String {
+    let template = format!(
+        "{}://{}{}",
+        url.scheme(),
+        url.host_str().unwrap_or_default(),
+        path_template(url)
+    );
+    message.replace(url.as_str(), &template)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(url: &str) -> String {
+        path_template(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn ids_are_replaced_in_paths() {
+        assert_eq!(
+            template("https://api.nylas.com/a/5tjrzbj2pz0k0gxwzpdapzmcs/accounts/awa6ltos76vz5hvphkp8k17nt/token-info"),
+            "/a/{id}/accounts/{id}/token-info"
+        );
+        assert_eq!(
+            template("https://api.nylas.com/messages/ax3ic1mdcdbbo2qd4jjm0ezwk?view=expanded"),
+            "/messages/{id}"
+        );
+        // Short IDs are recognized by their digits
+        assert_eq!(
+            template("https://api.nylas.com/a/app1/webhooks/w1"),
+            "/a/{id}/webhooks/{id}"
+        );
+    }
+
+    #[test]
+    fn endpoint_names_are_kept() {
+        for path in [
+            "/delta/latest_cursor",
+            "/delta/longpoll",
+            "/oauth/revoke",
+            "/connect/authorize",
+            "/messages",
+            "/a/{id}/accounts/{id}/revoke-all",
+        ] {
+            let url = format!("https://api.nylas.com{}", path.replace("{id}", "a1"));
+            assert_eq!(template(&url), path);
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn error_messages_leave_the_query_string_out() {
+        // Nothing listens on the port once the listener is dropped
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let url = Url::parse(&format!(
+            "http://127.0.0.1:{}/messages/m1?access_token=SECRET",
+            port
+        ))
+        .unwrap();
+        let error = reqwest::get(url.clone()).await.unwrap_err().to_string();
+        assert!(error.contains("SECRET"), "{}", error);
+
+        let message = redact_url(&error, &url);
+        assert!(!message.contains("SECRET"), "{}", message);
+        assert!(!message.contains('?'), "{}", message);
+        assert!(
+            message.contains("http://127.0.0.1/messages/{id}"),
+            "{}",
+            message
+        );
+    }
+}
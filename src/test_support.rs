@@ -0,0 +1,129 @@
+//! A local HTTP server standing in for Nylas in the tests.
+//!
+//! The server answers every connection with the next canned response and records the raw
+//! requests it received. Clients built with [`app`] send their requests to it through the
+//! [`Redirect`] middleware, so the hardcoded `https://api.nylas.com` URLs need no changes.
+
+use crate::client::Nylas;
+use crate::middleware::Middleware;
+use crate::retry::RetryPolicy;
+use reqwest::Request;
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// The raw requests received by the server, in order.
+pub(crate) type Received = Arc<Mutex<Vec<String>>>;
+
+/// Sends the requests to the local server instead of Nylas.
+pub(crate) struct Redirect(pub SocketAddr);
+
+impl Middleware for Redirect {
+    fn before_request(&self, request: &mut Request) {
+        let url = request.url_mut();
+        url.set_scheme("http").unwrap();
+        url.set_host(Some(&self.0.ip().to_string())).unwrap();
+        url.set_port(Some(self.0.port())).unwrap();
+    }
+}
+
+/// Answer every connection with the next response, recording the requests received.
+pub(crate) fn serve(responses: Vec<String>) -> (SocketAddr, Received) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let requests = received.clone();
+    thread::spawn(move || {
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_request(&mut stream);
+            requests.lock().unwrap().push(request);
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    (addr, received)
+}
+
+fn read_request(stream: &mut TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    let mut expected = None;
+    while expected.is_none_or(|expected| request.len() < expected) {
+        let read = stream.read(&mut buffer).unwrap();
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+        if expected.is_none() {
+            expected = body_end(&request);
+        }
+    }
+    String::from_utf8_lossy(&request).into_owned()
+}
+
+// The length of the request once its body is read, if its head is complete
+fn body_end(request: &[u8]) -> Option<usize> {
+    let head_end = request
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")?
+        + 4;
+    let head = String::from_utf8_lossy(&request[..head_end]).to_lowercase();
+    let length = head
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .map_or(0, |length| length.trim().parse().unwrap());
+    Some(head_end + length)
+}
+
+/// A JSON response with the given status line, e.g. `"200 OK"`.
+pub(crate) fn respond(status: &str, body: &str) -> String {
+    respond_with(status, &[], body)
+}
+
+/// A JSON response with extra headers.
+pub(crate) fn respond_with(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+    let headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}\r\n", name, value))
+        .collect();
+    format!(
+        "HTTP/1.1 {}\r\ncontent-type: application/json\r\n{}content-length: {}\r\n\
+         connection: close\r\n\r\n{}",
+        status,
+        headers,
+        body.len(),
+        body
+    )
+}
+
+/// A `200 OK` response holding `body`.
+pub(crate) fn ok(body: &Value) -> String {
+    respond("200 OK", &body.to_string())
+}
+
+/// The request line and the JSON body of a recorded request, `Value::Null` without a body.
+pub(crate) fn parse(request: &str) -> (&str, Value) {
+    let line = request.lines().next().unwrap();
+    let body = &request[request.find("\r\n\r\n").unwrap() + 4..];
+    (line, serde_json::from_str(body).unwrap_or(Value::Null))
+}
+
+/// The value of a header of a recorded request, whatever the case of its name.
+pub(crate) fn header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    let head = &request[..request.find("\r\n\r\n")?];
+    head.lines().skip(1).find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// An application client without access token sending its requests to the local server once.
+pub(crate) async fn app(addr: SocketAddr) -> Nylas {
+    Nylas::new("CLIENT_ID", "CLIENT_SECRET", None)
+        .await
+        .unwrap()
+        .with_retry_policy(RetryPolicy::never())
+        .with_middleware(Redirect(addr))
+}
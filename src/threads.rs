@@ -0,0 +1,31 @@
+use crate::messages::{EmailAddress, Folder, Label};
+use serde::{Deserialize, Serialize};
+
+/// Represents a thread, a group of messages belonging to the same conversation.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Thread {
+    pub id: String,
+    pub object: String,
+    pub account_id: String,
+    pub subject: Option<String>,
+    pub snippet: Option<String>,
+    pub unread: bool,
+    pub starred: bool,
+    pub has_attachments: bool,
+    pub version: i64,
+    pub first_message_timestamp: Option<i64>,
+    pub last_message_timestamp: Option<i64>,
+    pub last_message_received_timestamp: Option<i64>,
+    pub last_message_sent_timestamp: Option<i64>,
+    #[serde(default)]
+    pub participants: Vec<EmailAddress>,
+    #[serde(default)]
+    pub message_ids: Vec<String>,
+    #[serde(default)]
+    pub draft_ids: Vec<String>,
+    // only one of `folders` or `labels` is sent, depending on the account.
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+}
@@ -0,0 +1,84 @@
+//! Timestamps of the Nylas API.
+//!
+//! Nylas sends dates as Unix timestamps in seconds. [`Timestamp`] holds them on 64 bits, so
+//! dates after 2038 and before 1970 are read as well.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nylas::timestamp::Timestamp;
+//! use std::time::{Duration, UNIX_EPOCH};
+//!
+//! let timestamp: Timestamp = serde_json::from_str("4102444800").unwrap();
+//! assert_eq!(timestamp.as_secs(), 4102444800);
+//! let time = timestamp.to_system_time().unwrap();
+//! assert_eq!(time, UNIX_EPOCH + Duration::from_secs(4102444800));
+//! assert_eq!(Timestamp::from(time), timestamp);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A point in time, in seconds since the Unix epoch.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct Timestamp(pub i64);
+
+impl Timestamp {
+    pub fn now() -> Self {
+        Timestamp::from(SystemTime::now())
+    }
+
+    /// The number of seconds since the Unix epoch, negative before 1970.
+    pub fn as_secs(&self) -> i64 {
+        self.0
+    }
+
+    /// The point in time as a `SystemTime`, or `None` if the platform can't represent it.
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        let offset = Duration::from_secs(self.0.unsigned_abs());
+        if self.0 >= 0 {
+            UNIX_EPOCH.checked_add(offset)
+        } else {
+            UNIX_EPOCH.checked_sub(offset)
+        }
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => Timestamp(i64::try_from(elapsed.as_secs()).unwrap_or(i64::MAX)),
+            Err(error) => {
+                Timestamp(i64::try_from(error.duration().as_secs()).map_or(i64::MIN, |secs| -secs))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extreme_timestamps_convert_without_overflowing() {
+        // Whether the largest timestamps fit depends on the platform
+        for timestamp in [Timestamp(i64::MAX), Timestamp(i64::MIN)] {
+            if let Some(time) = timestamp.to_system_time() {
+                assert_eq!(Timestamp::from(time), timestamp);
+            }
+        }
+        assert_eq!(Timestamp(0).to_system_time(), Some(UNIX_EPOCH));
+        assert_eq!(
+            Timestamp(-86400).to_system_time(),
+            Some(UNIX_EPOCH - Duration::from_secs(86400))
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Access tokens provided at request time.
+//!
+//! A [`TokenProvider`] attached with `Nylas::with_token_provider` is asked for the access token
+//! before every request, instead of the fixed token given to `Nylas::new`. When Nylas rejects
+//! the token with `401 Unauthorized`, the provider is told with
+//! [`TokenProvider::invalidate`] and the request is sent once more with a fresh token.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nylas::client::Nylas;
+//! use nylas::token::RefreshingToken;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let app = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+//!
+//!     // The access token is refreshed whenever it expires or is rejected
+//!     let nylas = app
+//!         .clone()
+//!         .with_token_provider(RefreshingToken::new(&app, "YOUR_REFRESH_TOKEN"));
+//!
+//!     let messages = nylas.messages().all().await;
+//! }
+//! ```
+
+use crate::auth::TokenResponse;
+use crate::client::Nylas;
+use crate::timestamp::Timestamp;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// The future of an access token.
+pub type TokenFuture<'a> = Pin<Box<dyn Future<Output = Result<String, String>> + Send + 'a>>;
+
+/// Provides the access token sent with every request.
+pub trait TokenProvider: Send + Sync {
+    /// The access token to send with the next request.
+    fn token(&self) -> TokenFuture<'_>;
+
+    /// Called when Nylas rejected `token` with `401 Unauthorized`.
+    ///
+    /// Returns whether [`TokenProvider::token`] may now provide another token, in which case the
+    /// request is sent once more. Only tokens handed out by [`TokenProvider::token`] are passed.
+    fn invalidate(&self, _token: &str) -> bool {
+        false
+    }
+}
+
+/// A fixed access token.
+#[derive(Clone)]
+pub struct StaticToken {
+    token: String,
+}
+
+impl StaticToken {
+    pub fn new(token: &str) -> Self {
+        StaticToken {
+            token: token.to_string(),
+        }
+    }
+}
+
+impl fmt::Debug for StaticToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticToken")
+            .field("token", &"[REDACTED]")
+            .finish()
+    }
+}
+
+impl TokenProvider for StaticToken {
+    fn token(&self) -> TokenFuture<'_> {
+        Box::pin(async move { Ok(self.token.clone()) })
+    }
+}
+
+/// The last access tokens handed out, to recognize them once Nylas rejects them.
+#[derive(Default)]
+pub(crate) struct IssuedTokens(Mutex<VecDeque<String>>);
+
+impl IssuedTokens {
+    // Enough to cover the tokens still used by requests in flight while the token is refreshed
+    const CAPACITY: usize = 4;
+
+    pub(crate) fn record(&self, token: &str) {
+        let mut tokens = self.lock();
+        if tokens.iter().any(|issued| issued == token) {
+            return;
+        }
+        if tokens.len() == Self::CAPACITY {
+            tokens.pop_front();
+        }
+        tokens.push_back(token.to_string());
+    }
+
+    pub(crate) fn contains(&self, token: &str) -> bool {
+        self.lock().iter().any(|issued| issued == token)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<String>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+struct Tokens {
+    current: Option<TokenResponse>,
+    refresh_token: String,
+}
+
+/// An access token obtained with a refresh token, refreshed shortly before it expires or as
+/// soon as Nylas rejects it.
+///
+/// Concurrent requests wait for a single refresh. The refresh token is replaced when Nylas sends
+/// a new one along with the access token.
+pub struct RefreshingToken {
+    app: Nylas,
+    tokens: tokio::sync::Mutex<Tokens>,
+    rejected: Mutex<Option<String>>,
+    issued: IssuedTokens,
+    margin: Duration,
+}
+
+impl RefreshingToken {
+    /// Refresh access tokens with the client ID and secret of `app`.
+    pub fn new(app: &Nylas, refresh_token: &str) -> Self {
+        RefreshingToken {
+            app: app.clone(),
+            tokens: tokio::sync::Mutex::new(Tokens {
+                current: None,
+                refresh_token: refresh_token.to_string(),
+            }),
+            rejected: Mutex::new(None),
+            issued: IssuedTokens::default(),
+            margin: Duration::from_secs(60),
+        }
+    }
+
+    /// Start with the access token of a token exchange, refreshed with its refresh token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response has no refresh token.
+    pub fn from_response(app: &Nylas, token: TokenResponse) -> Result<Self, String> {
+        let refresh_token = token
+            .refresh_token
+            .clone()
+            .ok_or("Refresh token not found in the response.")?;
+        let mut provider = RefreshingToken::new(app, &refresh_token);
+        provider.tokens.get_mut().current = Some(token);
+        Ok(provider)
+    }
+
+    /// Refresh the access token this long before it expires, one minute by default.
+    pub fn with_margin(mut self, margin: Duration) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    fn is_fresh(&self, token: &TokenResponse) -> bool {
+        let rejected = self
+            .rejected
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if rejected.as_deref() == Some(token.access_token.as_str()) {
+            return false;
+        }
+        match token.expires_at {
+            Some(expires_at) => expires_at > Timestamp::from(SystemTime::now() + self.margin),
+            None => true,
+        }
+    }
+}
+
+impl fmt::Debug for RefreshingToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RefreshingToken")
+            .field("app", &self.app)
+            .field("margin", &self.margin)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TokenProvider for RefreshingToken {
+    fn token(&self) -> TokenFuture<'_> {
+        Box::pin(async move {
+            let mut tokens = self.tokens.lock().await;
+            if let Some(current) = &tokens.current {
+                if self.is_fresh(current) {
+                    self.issued.record(&current.access_token);
+                    return Ok(current.access_token.clone());
+                }
+            }
+
+            let mut refreshed = self.app.refresh_access_token(&tokens.refresh_token).await?;
+            if let Some(refresh_token) = refreshed.refresh_token.take() {
+                tokens.refresh_token = refresh_token;
+            }
+            let access_token = refreshed.access_token.clone();
+            self.issued.record(&access_token);
+            tokens.current = Some(refreshed);
+            Ok(access_token)
+        })
+    }
+
+    fn invalidate(&self, token: &str) -> bool {
+        // Tokens of other accounts or applications can't be replaced by refreshing this one
+        if !self.issued.contains(token) {
+            return false;
+        }
+        *self
+            .rejected
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(token.to_string());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app, respond, serve};
+    use reqwest::Method;
+    use std::sync::Arc;
+
+    fn token(access_token: &str, refresh_token: Option<&str>, expires_in: u64) -> String {
+        serde_json::json!({
+            "access_token": access_token,
+            "refresh_token": refresh_token,
+            "expires_in": expires_in,
+        })
+        .to_string()
+    }
+
+    fn response(access_token: &str, expires_in: u64) -> TokenResponse {
+        serde_json::from_str(&token(access_token, Some("r1"), expires_in)).unwrap()
+    }
+
+    fn unauthorized() -> String {
+        respond("401 Unauthorized", "{}")
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_refreshed_once_for_concurrent_callers() {
+        let (addr, received) = serve(vec![respond("200 OK", &token("new", None, 3600))]);
+        let app = app(addr).await;
+        let provider = Arc::new(RefreshingToken::from_response(&app, response("old", 0)).unwrap());
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let provider = provider.clone();
+                tokio::spawn(async move { provider.token().await })
+            })
+            .collect();
+        for task in tasks {
+            assert_eq!(task.await.unwrap().unwrap(), "new");
+        }
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert!(received[0].contains("grant_type=refresh_token"));
+        assert!(received[0].contains("refresh_token=r1"));
+    }
+
+    #[tokio::test]
+    async fn unauthorized_request_is_retried_once_with_a_new_token() {
+        let (addr, received) = serve(vec![
+            unauthorized(),
+            respond("200 OK", &token("new", None, 3600)),
+            unauthorized(),
+        ]);
+        let app = app(addr).await;
+        let provider = RefreshingToken::from_response(&app, response("old", 3600)).unwrap();
+        let nylas = app.clone().with_token_provider(provider);
+
+        let request = nylas
+            .request(Method::GET, "https://api.nylas.com/messages")
+            .await
+            .unwrap();
+        let response = nylas.send(request).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        assert!(received[0].contains("Bearer old"));
+        assert!(received[1].starts_with("POST /oauth/token"));
+        assert!(received[2].contains("Bearer new"));
+    }
+
+    #[tokio::test]
+    async fn rotated_refresh_token_replaces_the_old_one() {
+        let (addr, received) = serve(vec![
+            respond("200 OK", &token("a1", Some("r2"), 0)),
+            respond("200 OK", &token("a2", None, 3600)),
+        ]);
+        let app = app(addr).await;
+        let provider = RefreshingToken::new(&app, "r1");
+
+        assert_eq!(provider.token().await.unwrap(), "a1");
+        assert_eq!(provider.token().await.unwrap(), "a2");
+        assert_eq!(provider.token().await.unwrap(), "a2");
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert!(received[0].contains("refresh_token=r1"));
+        assert!(received[1].contains("refresh_token=r2"));
+    }
+
+    #[tokio::test]
+    async fn foreign_bearer_token_is_not_retried() {
+        let (addr, received) = serve(vec![unauthorized(), unauthorized()]);
+        let app = app(addr).await;
+        let provider = RefreshingToken::from_response(&app, response("mine", 3600)).unwrap();
+        assert!(!provider.invalidate("other"));
+        let nylas = app.clone().with_token_provider(provider);
+
+        assert!(nylas.revoke_token("other").await.is_err());
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert!(received[0].contains("Bearer other"));
+    }
+}
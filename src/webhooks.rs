@@ -0,0 +1,1271 @@
+use crate::client::Nylas;
+use crate::messages::{Event, Message};
+use hmac::{Hmac, Mac};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use url::{form_urlencoded, Url};
+
+/// The header holding the HMAC-SHA256 signature of a webhook notification.
+pub const SIGNATURE_HEADER: &str = "X-Nylas-Signature";
+
+/// Verifies the `X-Nylas-Signature` of a webhook notification.
+///
+/// The signature is the hex encoded HMAC-SHA256 of the raw request body, keyed with the client
+/// secret of the application. The comparison is done in constant time.
+///
+/// # Arguments
+///
+/// - `secret`: The client secret of the application.
+/// - `body`: The raw body of the notification, exactly as received.
+/// - `signature`: The value of the `X-Nylas-Signature` header.
+///
+/// # Returns
+///
+/// `true` if the signature is valid for the body, `false` otherwise.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::webhooks::verify_signature;
+///
+/// let body = br#"{"deltas":[]}"#;
+/// let signature = "515ae7ed48c38ebcb9fc81a2c0541ffd82a0435fa2c57a856b3f35483803aba9";
+///
+/// assert!(verify_signature("YOUR_CLIENT_SECRET", body, signature));
+/// assert!(!verify_signature("ANOTHER_SECRET", body, signature));
+/// ```
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let signature = match hex::decode(signature.trim()) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+
+    let mut mac = match Hmac::<Sha256>::new_from_slice(secret.as_bytes()) {
+        Ok(mac) => mac,
+        Err(_) => return false,
+    };
+    mac.update(body);
+
+    // `verify_slice` compares the tags in constant time
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Answers the challenge Nylas sends with a `GET` request when a webhook is created.
+///
+/// Nylas calls the callback URL with a `challenge` query parameter and expects the endpoint to
+/// respond `200 OK` with the exact value of the parameter as body.
+///
+/// # Arguments
+///
+/// - `query`: The raw query string of the request, without the leading `?`.
+///
+/// # Returns
+///
+/// `Some(challenge)` holding the body to respond with, or `None` if the request isn't a challenge.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::webhooks::challenge_response;
+///
+/// assert_eq!(challenge_response("challenge=abc%2B123"), Some("abc+123".to_string()));
+/// assert_eq!(challenge_response("foo=bar"), None);
+/// ```
+pub fn challenge_response(query: &str) -> Option<String> {
+    form_urlencoded::parse(query.trim_start_matches('?').as_bytes())
+        .find(|(key, _)| key == "challenge")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Represents the state of a webhook.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookState {
+    Active,
+    Inactive,
+    Failing,
+    Failed,
+}
+
+/// Represents an event a webhook can be subscribed to.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WebhookTrigger {
+    #[serde(rename = "account.connected")]
+    AccountConnected,
+    #[serde(rename = "account.running")]
+    AccountRunning,
+    #[serde(rename = "account.stopped")]
+    AccountStopped,
+    #[serde(rename = "account.invalid")]
+    AccountInvalid,
+    #[serde(rename = "account.sync_error")]
+    AccountSyncError,
+    #[serde(rename = "calendar.created")]
+    CalendarCreated,
+    #[serde(rename = "calendar.updated")]
+    CalendarUpdated,
+    #[serde(rename = "calendar.deleted")]
+    CalendarDeleted,
+    #[serde(rename = "contact.created")]
+    ContactCreated,
+    #[serde(rename = "contact.updated")]
+    ContactUpdated,
+    #[serde(rename = "contact.deleted")]
+    ContactDeleted,
+    #[serde(rename = "event.created")]
+    EventCreated,
+    #[serde(rename = "event.updated")]
+    EventUpdated,
+    #[serde(rename = "event.deleted")]
+    EventDeleted,
+    #[serde(rename = "folder.created")]
+    FolderCreated,
+    #[serde(rename = "folder.updated")]
+    FolderUpdated,
+    #[serde(rename = "folder.deleted")]
+    FolderDeleted,
+    #[serde(rename = "label.created")]
+    LabelCreated,
+    #[serde(rename = "label.updated")]
+    LabelUpdated,
+    #[serde(rename = "label.deleted")]
+    LabelDeleted,
+    #[serde(rename = "message.created")]
+    MessageCreated,
+    #[serde(rename = "message.updated")]
+    MessageUpdated,
+    #[serde(rename = "message.opened")]
+    MessageOpened,
+    #[serde(rename = "message.link_clicked")]
+    MessageLinkClicked,
+    #[serde(rename = "thread.replied")]
+    ThreadReplied,
+    #[serde(rename = "job.successful")]
+    JobSuccessful,
+    #[serde(rename = "job.failed")]
+    JobFailed,
+    /// A trigger not known by this version of the SDK. It can't be subscribed to.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Represents a webhook registered for the application.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Webhook {
+    pub id: String,
+    pub application_id: String,
+    pub callback_url: String,
+    pub state: WebhookState,
+    pub triggers: Vec<WebhookTrigger>,
+    pub version: String,
+}
+
+/// Struct for managing the webhooks of the application.
+///
+/// Webhooks are application-level resources: requests are authenticated with the client ID and
+/// client secret stored on `Nylas` and don't require an access token.
+pub struct Webhooks<'a> {
+    pub nylas: &'a Nylas,
+}
+
+impl<'a> Webhooks<'a> {
+    pub fn new(nylas: &'a Nylas) -> Self {
+        Webhooks { nylas }
+    }
+
+    /// Retrieve all webhooks of the application.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of webhooks if successful, or an error message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use nylas::client::Nylas;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client_id = "YOUR_CLIENT_ID";
+    ///     let client_secret = "YOUR_CLIENT_SECRET";
+    ///
+    ///     let nylas = Nylas::new(client_id, client_secret, None).await.unwrap();
+    ///
+    ///     match nylas.webhooks().all().await {
+    ///         Ok(webhooks) => {
+    ///             for webhook in webhooks {
+    ///                 println!("{} -> {:?}", webhook.callback_url, webhook.state);
+    ///             }
+    ///         }
+    ///         Err(err) => eprintln!("Error: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn all(&self) -> Result<Vec<Webhook>, String> {
+        let url = self.url(None)?;
+
+        let request = self.nylas.send(self.nylas.app_request(Method::GET, &url));
+
+        match request.await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    response
+                        .json()
+                        .await
+                        .map_err(|e| format!("JSON Parsing Error: {:?}", e))
+                } else {
+                    Err(format!("Request failed with status: {}", response.status()))
+                }
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// Retrieve a specific webhook by its ID.
+    ///
+    /// # Arguments
+    ///
+    /// - `id`: The ID of the webhook.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `Some(webhook)` if found, `None` if the webhook doesn't exist, or an
+    /// error message.
+    pub async fn get(&self, id: &str) -> Result<Option<Webhook>, String> {
+        let url = self.url(Some(id))?;
+
+        let request = self.nylas.send(self.nylas.app_request(Method::GET, &url));
+
+        match request.await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    let webhook: Webhook = response
+                        .json()
+                        .await
+                        .map_err(|e| format!("JSON Parsing Error: {:?}", e))?;
+                    Ok(Some(webhook))
+                } else if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    Ok(None)
+                } else {
+                    Err(format!("Request failed with status: {}", response.status()))
+                }
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// Register a new webhook.
+    ///
+    /// # Arguments
+    ///
+    /// - `callback_url`: The URL Nylas will send notifications to. It must be reachable over HTTPS.
+    /// - `triggers`: The events the webhook is subscribed to.
+    /// - `state`: An optional initial state, `active` by default.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the created webhook if successful, or an error message.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error without sending the request if `triggers` is empty or contains
+    /// [`WebhookTrigger::Unknown`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use nylas::client::Nylas;
+    /// use nylas::webhooks::WebhookTrigger;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", None).await.unwrap();
+    ///
+    ///     let webhook = nylas
+    ///         .webhooks()
+    ///         .create(
+    ///             "https://example.com/webhooks/nylas",
+    ///             &[WebhookTrigger::MessageCreated, WebhookTrigger::ThreadReplied],
+    ///             None,
+    ///         )
+    ///         .await;
+    /// }
+    /// ```
+    pub async fn create(
+        &self,
+        callback_url: &str,
+        triggers: &[WebhookTrigger],
+        state: Option<WebhookState>,
+    ) -> Result<Webhook, String> {
+        if triggers.is_empty() {
+            return Err("At least one trigger must be provided.".to_string());
+        }
+        if triggers.contains(&WebhookTrigger::Unknown) {
+            return Err("Unknown triggers can't be subscribed to.".to_string());
+        }
+
+        let url = self.url(None)?;
+        let body = json!({
+            "callback_url": callback_url,
+            "triggers": triggers,
+            "state": state.unwrap_or(WebhookState::Active),
+        });
+
+        let request = self
+            .nylas
+            .send(self.nylas.app_request(Method::POST, &url).json(&body));
+
+        match request.await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    response
+                        .json()
+                        .await
+                        .map_err(|e| format!("JSON Parsing Error: {:?}", e))
+                } else {
+                    Err(format!("Request failed with status: {}", response.status()))
+                }
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// Update the state of a webhook, e.g. to pause or resume notifications.
+    ///
+    /// # Arguments
+    ///
+    /// - `id`: The ID of the webhook.
+    /// - `state`: The new state, either `active` or `inactive`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated webhook if successful, or an error message.
+    pub async fn update(&self, id: &str, state: WebhookState) -> Result<Webhook, String> {
+        if !matches!(state, WebhookState::Active | WebhookState::Inactive) {
+            return Err("A webhook can only be set to active or inactive.".to_string());
+        }
+
+        let url = self.url(Some(id))?;
+
+        let request = self.nylas.send(
+            self.nylas
+                .app_request(Method::PUT, &url)
+                .json(&json!({ "state": state })),
+        );
+
+        match request.await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    response
+                        .json()
+                        .await
+                        .map_err(|e| format!("JSON Parsing Error: {:?}", e))
+                } else {
+                    Err(format!("Request failed with status: {}", response.status()))
+                }
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// Delete a webhook.
+    ///
+    /// # Arguments
+    ///
+    /// - `id`: The ID of the webhook.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok(())` if the webhook was deleted, or an error message.
+    pub async fn delete(&self, id: &str) -> Result<(), String> {
+        let url = self.url(Some(id))?;
+
+        let request = self
+            .nylas
+            .send(self.nylas.app_request(Method::DELETE, &url));
+
+        match request.await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    Ok(())
+                } else {
+                    Err(format!("Request failed with status: {}", response.status()))
+                }
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn url(&self, id: Option<&str>) -> Result<String, String> {
+        if self.nylas.client_id().is_empty() || self.nylas.client_secret().is_empty() {
+            return Err("Client ID and Client Secret must not be empty.".to_string());
+        }
+
+        // Each segment is percent-encoded, so an ID can't change the path
+        let mut url = Url::parse("https://api.nylas.com/a").map_err(|e| e.to_string())?;
+        url.path_segments_mut()
+            .map_err(|_| "Cannot add segments to the webhooks URL.".to_string())?
+            .push(self.nylas.client_id())
+            .push("webhooks")
+            .extend(id);
+        Ok(url.to_string())
+    }
+}
+
+/// Represents the body of a webhook notification.
+///
+/// # Examples
+///
+/// ```rust
+/// use nylas::webhooks::{Delta, WebhookNotification};
+///
+/// let body = br#"{
+///     "deltas": [{
+///         "date": 1602623196,
+///         "object": "message",
+///         "type": "message.created",
+///         "object_data": {
+///             "namespace_id": "aaz875kwuvxik6ku7pwkqp3ah",
+///             "account_id": "aaz875kwuvxik6ku7pwkqp3ah",
+///             "object": "message",
+///             "attributes": {
+///                 "thread_id": "2u152dt4tnq9j61j8seg26ni6",
+///                 "received_date": 1602623166
+///             },
+///             "id": "93mgpjynqqu5fohl2dvv6ray7",
+///             "metadata": null
+///         }
+///     }]
+/// }"#;
+///
+/// let notification = WebhookNotification::from_slice(body).unwrap();
+/// for delta in &notification.deltas {
+///     if let Delta::MessageCreated(data) = delta {
+///         println!("New message {}", data.object_data.id);
+///     }
+/// }
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebhookNotification {
+    pub deltas: Vec<Delta>,
+}
+
+impl WebhookNotification {
+    /// Parse a webhook notification from the raw request body.
+    ///
+    /// The signature of the body should be checked with [`verify_signature`] beforehand.
+    pub fn from_slice(body: &[u8]) -> Result<Self, String> {
+        serde_json::from_slice(body).map_err(|e| format!("JSON Parsing Error: {:?}", e))
+    }
+}
+
+/// Represents a single change reported by a webhook notification.
+///
+/// The variant is selected by the `type` of the delta, and carries the typed `object_data`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type")]
+pub enum Delta {
+    #[serde(rename = "message.created")]
+    MessageCreated(DeltaData),
+    #[serde(rename = "message.updated")]
+    MessageUpdated(DeltaData),
+    #[serde(rename = "message.opened")]
+    MessageOpened(DeltaData<MessageOpenedMetadata>),
+    #[serde(rename = "message.link_clicked")]
+    MessageLinkClicked(DeltaData<LinkClickedMetadata>),
+    #[serde(rename = "thread.replied")]
+    ThreadReplied(DeltaData<ThreadRepliedMetadata>),
+    #[serde(rename = "event.created")]
+    EventCreated(DeltaData),
+    #[serde(rename = "event.updated")]
+    EventUpdated(DeltaData),
+    #[serde(rename = "event.deleted")]
+    EventDeleted(DeltaData),
+    #[serde(rename = "calendar.created")]
+    CalendarCreated(DeltaData),
+    #[serde(rename = "calendar.updated")]
+    CalendarUpdated(DeltaData),
+    #[serde(rename = "calendar.deleted")]
+    CalendarDeleted(DeltaData),
+    #[serde(rename = "contact.created")]
+    ContactCreated(DeltaData),
+    #[serde(rename = "contact.updated")]
+    ContactUpdated(DeltaData),
+    #[serde(rename = "contact.deleted")]
+    ContactDeleted(DeltaData),
+    #[serde(rename = "account.connected")]
+    AccountConnected(DeltaData),
+    #[serde(rename = "account.running")]
+    AccountRunning(DeltaData),
+    #[serde(rename = "account.stopped")]
+    AccountStopped(DeltaData),
+    #[serde(rename = "account.invalid")]
+    AccountInvalid(DeltaData),
+    #[serde(rename = "account.sync_error")]
+    AccountSyncError(DeltaData),
+    #[serde(rename = "folder.created")]
+    FolderCreated(DeltaData),
+    #[serde(rename = "folder.updated")]
+    FolderUpdated(DeltaData),
+    #[serde(rename = "folder.deleted")]
+    FolderDeleted(DeltaData),
+    #[serde(rename = "label.created")]
+    LabelCreated(DeltaData),
+    #[serde(rename = "label.updated")]
+    LabelUpdated(DeltaData),
+    #[serde(rename = "label.deleted")]
+    LabelDeleted(DeltaData),
+    #[serde(rename = "job.successful")]
+    JobSuccessful(DeltaData),
+    #[serde(rename = "job.failed")]
+    JobFailed(DeltaData),
+    /// A delta type not known by this version of the SDK.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Represents the content of a delta.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeltaData<M = Value> {
+    /// The Unix timestamp of the change.
+    pub date: i64,
+    /// The type of the changed object, e.g. `message`, `event` or `account`.
+    pub object: String,
+    pub object_data: ObjectData<M>,
+}
+
+/// Represents the object a delta refers to.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ObjectData<M = Value> {
+    pub id: String,
+    pub account_id: String,
+    pub namespace_id: Option<String>,
+    pub object: String,
+    pub attributes: Option<ObjectAttributes>,
+    pub metadata: Option<M>,
+}
+
+/// Represents the attributes sent along with the object of a delta.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ObjectAttributes {
+    pub thread_id: Option<String>,
+    pub received_date: Option<i64>,
+    pub calendar_id: Option<String>,
+    /// Any other attribute sent by Nylas.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Represents a recent open or click of a tracked message.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecentActivity {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub timestamp: Option<i64>,
+    pub link_index: Option<i64>,
+    pub id: Option<i64>,
+}
+
+/// Represents the metadata of a `message.opened` delta.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageOpenedMetadata {
+    pub message_id: String,
+    pub sender_app_id: Option<i64>,
+    pub payload: Option<String>,
+    pub timestamp: Option<i64>,
+    pub count: Option<i64>,
+    #[serde(default)]
+    pub recents: Vec<RecentActivity>,
+}
+
+/// Represents a tracked link and the number of times it was clicked.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LinkData {
+    pub url: String,
+    pub count: i64,
+}
+
+/// Represents the metadata of a `message.link_clicked` delta.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LinkClickedMetadata {
+    pub message_id: String,
+    pub sender_app_id: Option<i64>,
+    pub payload: Option<String>,
+    pub timestamp: Option<i64>,
+    #[serde(default)]
+    pub link_data: Vec<LinkData>,
+    #[serde(default)]
+    pub recents: Vec<RecentActivity>,
+}
+
+/// Represents the metadata of a `thread.replied` delta.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThreadRepliedMetadata {
+    /// The ID of the reply.
+    pub message_id: String,
+    pub thread_id: String,
+    /// The ID of the tracked message that was replied to.
+    pub reply_to_message_id: Option<String>,
+    #[serde(default)]
+    pub from_self: bool,
+    pub sender_app_id: Option<i64>,
+    pub payload: Option<String>,
+    pub timestamp: Option<i64>,
+}
+
+impl Delta {
+    /// The trigger which caused this delta.
+    pub fn trigger(&self) -> WebhookTrigger {
+        match self {
+            Delta::MessageCreated(_) => WebhookTrigger::MessageCreated,
+            Delta::MessageUpdated(_) => WebhookTrigger::MessageUpdated,
+            Delta::MessageOpened(_) => WebhookTrigger::MessageOpened,
+            Delta::MessageLinkClicked(_) => WebhookTrigger::MessageLinkClicked,
+            Delta::ThreadReplied(_) => WebhookTrigger::ThreadReplied,
+            Delta::EventCreated(_) => WebhookTrigger::EventCreated,
+            Delta::EventUpdated(_) => WebhookTrigger::EventUpdated,
+            Delta::EventDeleted(_) => WebhookTrigger::EventDeleted,
+            Delta::CalendarCreated(_) => WebhookTrigger::CalendarCreated,
+            Delta::CalendarUpdated(_) => WebhookTrigger::CalendarUpdated,
+            Delta::CalendarDeleted(_) => WebhookTrigger::CalendarDeleted,
+            Delta::ContactCreated(_) => WebhookTrigger::ContactCreated,
+            Delta::ContactUpdated(_) => WebhookTrigger::ContactUpdated,
+            Delta::ContactDeleted(_) => WebhookTrigger::ContactDeleted,
+            Delta::AccountConnected(_) => WebhookTrigger::AccountConnected,
+            Delta::AccountRunning(_) => WebhookTrigger::AccountRunning,
+            Delta::AccountStopped(_) => WebhookTrigger::AccountStopped,
+            Delta::AccountInvalid(_) => WebhookTrigger::AccountInvalid,
+            Delta::AccountSyncError(_) => WebhookTrigger::AccountSyncError,
+            Delta::FolderCreated(_) => WebhookTrigger::FolderCreated,
+            Delta::FolderUpdated(_) => WebhookTrigger::FolderUpdated,
+            Delta::FolderDeleted(_) => WebhookTrigger::FolderDeleted,
+            Delta::LabelCreated(_) => WebhookTrigger::LabelCreated,
+            Delta::LabelUpdated(_) => WebhookTrigger::LabelUpdated,
+            Delta::LabelDeleted(_) => WebhookTrigger::LabelDeleted,
+            Delta::JobSuccessful(_) => WebhookTrigger::JobSuccessful,
+            Delta::JobFailed(_) => WebhookTrigger::JobFailed,
+            Delta::Unknown => WebhookTrigger::Unknown,
+        }
+    }
+
+    /// The Unix timestamp of the change, or `None` for unknown deltas.
+    pub fn date(&self) -> Option<i64> {
+        self.header().map(|(date, _, _)| date)
+    }
+
+    /// The ID of the object this delta refers to, or `None` for unknown deltas.
+    pub fn object_id(&self) -> Option<&str> {
+        self.header().map(|(_, id, _)| id)
+    }
+
+    /// The ID of the account this delta belongs to, or `None` for unknown deltas.
+    pub fn account_id(&self) -> Option<&str> {
+        self.header().map(|(_, _, account_id)| account_id)
+    }
+
+    /// The ID of the message this delta refers to, if any.
+    ///
+    /// For tracking deltas (`message.opened`, `message.link_clicked`, `thread.replied`) the ID is
+    /// read from the metadata, since the object of the delta is the tracking metadata itself.
+    pub fn message_id(&self) -> Option<&str> {
+        match self {
+            Delta::MessageCreated(data) | Delta::MessageUpdated(data) => Some(&data.object_data.id),
+            Delta::MessageOpened(data) => data
+                .object_data
+                .metadata
+                .as_ref()
+                .map(|metadata| metadata.message_id.as_str()),
+            Delta::MessageLinkClicked(data) => data
+                .object_data
+                .metadata
+                .as_ref()
+                .map(|metadata| metadata.message_id.as_str()),
+            Delta::ThreadReplied(data) => data
+                .object_data
+                .metadata
+                .as_ref()
+                .map(|metadata| metadata.message_id.as_str()),
+            _ => None,
+        }
+    }
+
+    /// The ID of the calendar event this delta refers to, if any.
+    pub fn event_id(&self) -> Option<&str> {
+        match self {
+            Delta::EventCreated(data) | Delta::EventUpdated(data) | Delta::EventDeleted(data) => {
+                Some(&data.object_data.id)
+            }
+            _ => None,
+        }
+    }
+
+    /// Fetch the message this delta refers to.
+    ///
+    /// The `Nylas` instance must hold the access token of the account the delta belongs to.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `Some(message)` if the delta refers to an existing message, `None`
+    /// otherwise, or an error message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use nylas::client::Nylas;
+    /// use nylas::webhooks::WebhookNotification;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let access_token = "ACCESS_TOKEN_OF_THE_ACCOUNT";
+    ///     let nylas = Nylas::new("YOUR_CLIENT_ID", "YOUR_CLIENT_SECRET", Some(access_token))
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     let notification = WebhookNotification::from_slice(b"{\"deltas\":[]}").unwrap();
+    ///     for delta in &notification.deltas {
+    ///         if let Ok(Some(message)) = delta.message(&nylas).await {
+    ///             println!("{}", message.subject);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn message(&self, nylas: &Nylas) -> Result<Option<Message>, String> {
+        match self.message_id() {
+            Some(id) => nylas.messages().get(id, None).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Fetch the calendar event this delta refers to.
+    ///
+    /// The `Nylas` instance must hold the access token of the account the delta belongs to.
+    /// Deleted events can't be fetched and return `None`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `Some(event)` if the delta refers to an existing event, `None`
+    /// otherwise, or an error message.
+    pub async fn event(&self, nylas: &Nylas) -> Result<Option<Event>, String> {
+        match self {
+            Delta::EventDeleted(_) => Ok(None),
+            _ => match self.event_id() {
+                Some(id) => nylas.events().get(id).await,
+                None => Ok(None),
+            },
+        }
+    }
+
+    fn header(&self) -> Option<(i64, &str, &str)> {
+        match self {
+            Delta::MessageOpened(data) => Some((
+                data.date,
+                &data.object_data.id,
+                &data.object_data.account_id,
+            )),
+            Delta::MessageLinkClicked(data) => Some((
+                data.date,
+                &data.object_data.id,
+                &data.object_data.account_id,
+            )),
+            Delta::ThreadReplied(data) => Some((
+                data.date,
+                &data.object_data.id,
+                &data.object_data.account_id,
+            )),
+            Delta::MessageCreated(data)
+            | Delta::MessageUpdated(data)
+            | Delta::EventCreated(data)
+            | Delta::EventUpdated(data)
+            | Delta::EventDeleted(data)
+            | Delta::CalendarCreated(data)
+            | Delta::CalendarUpdated(data)
+            | Delta::CalendarDeleted(data)
+            | Delta::ContactCreated(data)
+            | Delta::ContactUpdated(data)
+            | Delta::ContactDeleted(data)
+            | Delta::AccountConnected(data)
+            | Delta::AccountRunning(data)
+            | Delta::AccountStopped(data)
+            | Delta::AccountInvalid(data)
+            | Delta::AccountSyncError(data)
+            | Delta::FolderCreated(data)
+            | Delta::FolderUpdated(data)
+            | Delta::FolderDeleted(data)
+            | Delta::LabelCreated(data)
+            | Delta::LabelUpdated(data)
+            | Delta::LabelDeleted(data)
+            | Delta::JobSuccessful(data)
+            | Delta::JobFailed(data) => Some((
+                data.date,
+                &data.object_data.id,
+                &data.object_data.account_id,
+            )),
+            Delta::Unknown => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app, header, ok, parse as request, respond, serve};
+
+    const SECRET: &str = "YOUR_CLIENT_SECRET";
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn valid_signatures_are_accepted() {
+        let body = br#"{"deltas":[]}"#;
+        let signature = sign(SECRET, body);
+        assert!(verify_signature(SECRET, body, &signature));
+        assert!(verify_signature(SECRET, body, &signature.to_uppercase()));
+        assert!(verify_signature(SECRET, body, &format!(" {}\n", signature)));
+    }
+
+    #[test]
+    fn wrong_secret_or_body_is_rejected() {
+        let body = br#"{"deltas":[]}"#;
+        let signature = sign(SECRET, body);
+        assert!(!verify_signature("ANOTHER_SECRET", body, &signature));
+        assert!(!verify_signature("", body, &signature));
+        assert!(!verify_signature(SECRET, br#"{"deltas":[{}]}"#, &signature));
+    }
+
+    #[test]
+    fn truncated_or_malformed_signatures_are_rejected() {
+        let body = br#"{"deltas":[]}"#;
+        let signature = sign(SECRET, body);
+        for malformed in [
+            &signature[..signature.len() - 2],
+            &signature[..signature.len() - 1],
+            &signature[..32],
+            "",
+            "not a signature",
+            &format!("{}00", signature),
+            &format!("{}zz", &signature[..signature.len() - 2]),
+            &format!("sha256={}", signature),
+        ] {
+            assert!(!verify_signature(SECRET, body, malformed), "{}", malformed);
+        }
+    }
+
+    #[test]
+    fn empty_bodies_are_signed_too() {
+        let signature = sign(SECRET, b"");
+        assert!(verify_signature(SECRET, b"", &signature));
+        assert!(!verify_signature(SECRET, b"", ""));
+        assert!(!verify_signature(SECRET, b"{}", &signature));
+    }
+
+    #[test]
+    fn challenges_are_percent_decoded() {
+        assert_eq!(
+            challenge_response("challenge=abc%2B123%20%2F%3D"),
+            Some("abc+123 /=".to_string())
+        );
+        assert_eq!(
+            challenge_response("?foo=bar&challenge=a+b"),
+            Some("a b".to_string())
+        );
+        assert_eq!(challenge_response("challenge="), Some(String::new()));
+        assert_eq!(challenge_response("challenges=abc"), None);
+        assert_eq!(challenge_response(""), None);
+    }
+
+    fn delta(kind: &str, object: &str, metadata: Value) -> Value {
+        json!({
+            "date": 1602623196,
+            "object": object,
+            "type": kind,
+            "object_data": {
+                "namespace_id": "n1",
+                "account_id": "a1",
+                "object": object,
+                "attributes": {"thread_id": "t1", "received_date": 1602623166},
+                "id": "o1",
+                "metadata": metadata,
+            },
+        })
+    }
+
+    fn parse(delta: Value) -> Delta {
+        let body = json!({ "deltas": [delta] }).to_string();
+        let mut notification = WebhookNotification::from_slice(body.as_bytes()).unwrap();
+        notification.deltas.remove(0)
+    }
+
+    #[test]
+    fn deltas_are_parsed_by_type() {
+        for (kind, object, trigger) in [
+            ("message.created", "message", WebhookTrigger::MessageCreated),
+            ("message.updated", "message", WebhookTrigger::MessageUpdated),
+            ("event.created", "event", WebhookTrigger::EventCreated),
+            ("event.updated", "event", WebhookTrigger::EventUpdated),
+            ("event.deleted", "event", WebhookTrigger::EventDeleted),
+            (
+                "calendar.created",
+                "calendar",
+                WebhookTrigger::CalendarCreated,
+            ),
+            (
+                "calendar.updated",
+                "calendar",
+                WebhookTrigger::CalendarUpdated,
+            ),
+            (
+                "calendar.deleted",
+                "calendar",
+                WebhookTrigger::CalendarDeleted,
+            ),
+            ("contact.created", "contact", WebhookTrigger::ContactCreated),
+            ("contact.updated", "contact", WebhookTrigger::ContactUpdated),
+            ("contact.deleted", "contact", WebhookTrigger::ContactDeleted),
+            (
+                "account.connected",
+                "account",
+                WebhookTrigger::AccountConnected,
+            ),
+            ("account.running", "account", WebhookTrigger::AccountRunning),
+            ("account.stopped", "account", WebhookTrigger::AccountStopped),
+            ("account.invalid", "account", WebhookTrigger::AccountInvalid),
+            (
+                "account.sync_error",
+                "account",
+                WebhookTrigger::AccountSyncError,
+            ),
+            ("folder.created", "folder", WebhookTrigger::FolderCreated),
+            ("folder.updated", "folder", WebhookTrigger::FolderUpdated),
+            ("folder.deleted", "folder", WebhookTrigger::FolderDeleted),
+            ("label.created", "label", WebhookTrigger::LabelCreated),
+            ("label.updated", "label", WebhookTrigger::LabelUpdated),
+            ("label.deleted", "label", WebhookTrigger::LabelDeleted),
+        ] {
+            let delta = parse(delta(kind, object, Value::Null));
+            assert_eq!(delta.trigger(), trigger, "{}", kind);
+            assert_eq!(delta.date(), Some(1602623196));
+            assert_eq!(delta.object_id(), Some("o1"));
+            assert_eq!(delta.account_id(), Some("a1"));
+            assert_eq!(
+                serde_json::to_value(trigger).unwrap(),
+                Value::String(kind.to_string())
+            );
+
+            let is_message = object == "message";
+            assert_eq!(delta.message_id(), is_message.then_some("o1"));
+            let is_event = object == "event";
+            assert_eq!(delta.event_id(), is_event.then_some("o1"));
+        }
+    }
+
+    #[test]
+    fn tracking_deltas_read_their_metadata() {
+        let opened = parse(delta(
+            "message.opened",
+            "metadata",
+            json!({
+                "message_id": "m1", "sender_app_id": 64, "payload": "campaign", "timestamp": 1,
+                "count": 2, "recents": [{"ip": "127.0.0.1", "user_agent": "Mozilla",
+                                         "timestamp": 1, "id": 0}],
+            }),
+        ));
+        match &opened {
+            Delta::MessageOpened(data) => {
+                let metadata = data.object_data.metadata.as_ref().unwrap();
+                assert_eq!(metadata.count, Some(2));
+                assert_eq!(metadata.recents[0].ip.as_deref(), Some("127.0.0.1"));
+            }
+            other => panic!("unexpected delta: {:?}", other),
+        }
+        assert_eq!(opened.message_id(), Some("m1"));
+
+        let clicked = parse(delta(
+            "message.link_clicked",
+            "metadata",
+            json!({
+                "message_id": "m2", "link_data": [{"url": "https://example.com", "count": 3}],
+            }),
+        ));
+        match &clicked {
+            Delta::MessageLinkClicked(data) => {
+                let metadata = data.object_data.metadata.as_ref().unwrap();
+                assert_eq!(metadata.link_data[0].url, "https://example.com");
+                assert!(metadata.recents.is_empty());
+            }
+            other => panic!("unexpected delta: {:?}", other),
+        }
+        assert_eq!(clicked.message_id(), Some("m2"));
+
+        let replied = parse(delta(
+            "thread.replied",
+            "metadata",
+            json!({"message_id": "m3", "thread_id": "t1", "reply_to_message_id": "m1"}),
+        ));
+        match &replied {
+            Delta::ThreadReplied(data) => {
+                let metadata = data.object_data.metadata.as_ref().unwrap();
+                assert_eq!(metadata.reply_to_message_id.as_deref(), Some("m1"));
+                assert!(!metadata.from_self);
+            }
+            other => panic!("unexpected delta: {:?}", other),
+        }
+        assert_eq!(replied.message_id(), Some("m3"));
+    }
+
+    #[test]
+    fn optional_fields_may_be_null() {
+        let delta = parse(json!({
+            "date": 1602623196,
+            "object": "message",
+            "type": "message.opened",
+            "object_data": {
+                "namespace_id": null,
+                "account_id": "a1",
+                "object": "metadata",
+                "attributes": null,
+                "id": "o1",
+                "metadata": {
+                    "message_id": "m1", "sender_app_id": null, "payload": null,
+                    "timestamp": null, "count": null,
+                    "recents": [{"ip": null, "user_agent": null, "timestamp": null,
+                                 "link_index": null, "id": null}],
+                },
+            },
+        }));
+        match &delta {
+            Delta::MessageOpened(data) => {
+                assert_eq!(data.object_data.namespace_id, None);
+                assert!(data.object_data.attributes.is_none());
+                let metadata = data.object_data.metadata.as_ref().unwrap();
+                assert_eq!(metadata.payload, None);
+                assert_eq!(metadata.recents[0].user_agent, None);
+            }
+            other => panic!("unexpected delta: {:?}", other),
+        }
+
+        let delta = parse(json!({
+            "date": 1602623196,
+            "object": "message",
+            "type": "message.created",
+            "object_data": {
+                "account_id": "a1",
+                "object": "message",
+                "attributes": {"thread_id": null, "received_date": null, "calendar_id": null,
+                               "extra": 1},
+                "id": "o1",
+            },
+        }));
+        match &delta {
+            Delta::MessageCreated(data) => {
+                let attributes = data.object_data.attributes.as_ref().unwrap();
+                assert_eq!(attributes.thread_id, None);
+                assert_eq!(attributes.received_date, None);
+                assert_eq!(attributes.extra.get("extra"), Some(&json!(1)));
+                assert!(data.object_data.metadata.is_none());
+            }
+            other => panic!("unexpected delta: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn job_deltas_have_their_trigger() {
+        let successful = parse(delta("job.successful", "job_status", Value::Null));
+        assert!(matches!(successful, Delta::JobSuccessful(_)));
+        assert_eq!(successful.trigger(), WebhookTrigger::JobSuccessful);
+        assert_eq!(successful.object_id(), Some("o1"));
+        assert_eq!(successful.account_id(), Some("a1"));
+
+        let failed = parse(delta("job.failed", "job_status", Value::Null));
+        assert!(matches!(failed, Delta::JobFailed(_)));
+        assert_eq!(failed.trigger(), WebhookTrigger::JobFailed);
+        assert_eq!(failed.message_id(), None);
+    }
+
+    #[test]
+    fn unknown_types_fall_back_to_unknown() {
+        let bounced = parse(delta("message.bounced", "message", Value::Null));
+        assert!(matches!(bounced, Delta::Unknown));
+        assert_eq!(bounced.trigger(), WebhookTrigger::Unknown);
+        assert_eq!(bounced.date(), None);
+        assert_eq!(bounced.object_id(), None);
+        assert_eq!(bounced.message_id(), None);
+
+        // Unknown deltas don't fail the notification
+        let body = json!({"deltas": [
+            delta("message.bounced", "message", Value::Null),
+            delta("message.created", "message", Value::Null),
+        ]})
+        .to_string();
+        let notification = WebhookNotification::from_slice(body.as_bytes()).unwrap();
+        assert!(matches!(notification.deltas[0], Delta::Unknown));
+        assert!(matches!(notification.deltas[1], Delta::MessageCreated(_)));
+    }
+
+    #[test]
+    fn unknown_triggers_are_read_as_unknown() {
+        let triggers: Vec<WebhookTrigger> =
+            serde_json::from_value(json!(["message.created", "job.failed", "message.bounced"]))
+                .unwrap();
+        assert_eq!(
+            triggers,
+            vec![
+                WebhookTrigger::MessageCreated,
+                WebhookTrigger::JobFailed,
+                WebhookTrigger::Unknown
+            ]
+        );
+    }
+
+    fn webhook(state: &str) -> Value {
+        json!({
+            "id": "w1", "application_id": "CLIENT_ID",
+            "callback_url": "https://example.com/hooks", "state": state,
+            "triggers": ["message.created"], "version": "2.0"
+        })
+    }
+
+    #[tokio::test]
+    async fn webhooks_are_listed_with_the_application_credentials() {
+        let (addr, received) = serve(vec![ok(&json!([webhook("active")]))]);
+        let webhooks = app(addr).await.webhooks().all().await.unwrap();
+        assert_eq!(webhooks.len(), 1);
+        assert_eq!(webhooks[0].state, WebhookState::Active);
+        assert_eq!(webhooks[0].triggers, vec![WebhookTrigger::MessageCreated]);
+
+        let received = received.lock().unwrap();
+        assert_eq!(
+            request(&received[0]).0,
+            "GET /a/CLIENT_ID/webhooks HTTP/1.1"
+        );
+        // The client secret followed by an empty password
+        assert_eq!(
+            header(&received[0], "authorization"),
+            Some("Basic Q0xJRU5UX1NFQ1JFVDo=")
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_webhooks_are_none_and_ids_stay_one_segment() {
+        let (addr, received) = serve(vec![respond("404 Not Found", "{}")]);
+        assert!(app(addr)
+            .await
+            .webhooks()
+            .get("w/1?x")
+            .await
+            .unwrap()
+            .is_none());
+
+        let received = received.lock().unwrap();
+        assert_eq!(
+            request(&received[0]).0,
+            "GET /a/CLIENT_ID/webhooks/w%2F1%3Fx HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn webhooks_are_created_active_by_default() {
+        let (addr, received) = serve(vec![ok(&webhook("active"))]);
+        let webhook = app(addr)
+            .await
+            .webhooks()
+            .create(
+                "https://example.com/hooks",
+                &[WebhookTrigger::MessageCreated, WebhookTrigger::JobFailed],
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(webhook.id, "w1");
+
+        let received = received.lock().unwrap();
+        assert_eq!(
+            request(&received[0]),
+            (
+                "POST /a/CLIENT_ID/webhooks HTTP/1.1",
+                json!({
+                    "callback_url": "https://example.com/hooks",
+                    "triggers": ["message.created", "job.failed"],
+                    "state": "active",
+                })
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_or_unknown_triggers_are_rejected_before_sending() {
+        let (addr, received) = serve(vec![]);
+        let nylas = app(addr).await;
+        let webhooks = nylas.webhooks();
+        assert_eq!(
+            webhooks
+                .create("https://example.com/hooks", &[], None)
+                .await
+                .unwrap_err(),
+            "At least one trigger must be provided."
+        );
+        assert_eq!(
+            webhooks
+                .create(
+                    "https://example.com/hooks",
+                    &[WebhookTrigger::MessageCreated, WebhookTrigger::Unknown],
+                    Some(WebhookState::Inactive),
+                )
+                .await
+                .unwrap_err(),
+            "Unknown triggers can't be subscribed to."
+        );
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn updates_only_send_the_state() {
+        let (addr, received) = serve(vec![ok(&webhook("inactive"))]);
+        let nylas = app(addr).await;
+        let webhooks = nylas.webhooks();
+        let webhook = webhooks.update("w1", WebhookState::Inactive).await.unwrap();
+        assert_eq!(webhook.state, WebhookState::Inactive);
+
+        // Failing and failed are set by Nylas only
+        assert_eq!(
+            webhooks
+                .update("w1", WebhookState::Failed)
+                .await
+                .unwrap_err(),
+            "A webhook can only be set to active or inactive."
+        );
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(
+            request(&received[0]),
+            (
+                "PUT /a/CLIENT_ID/webhooks/w1 HTTP/1.1",
+                json!({ "state": "inactive" })
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn deletion_failures_are_errors() {
+        let (addr, received) = serve(vec![
+            respond("200 OK", "{}"),
+            respond("500 Internal Server Error", "{}"),
+        ]);
+        let nylas = app(addr).await;
+        assert_eq!(nylas.webhooks().delete("w1").await, Ok(()));
+        assert_eq!(
+            nylas.webhooks().delete("w1").await,
+            Err("Request failed with status: 500 Internal Server Error".to_string())
+        );
+
+        let received = received.lock().unwrap();
+        assert_eq!(
+            request(&received[0]).0,
+            "DELETE /a/CLIENT_ID/webhooks/w1 HTTP/1.1"
+        );
+    }
+}